@@ -16,6 +16,8 @@ pub mod names {
     pub const SECRET_KEY: &'static str = "secret_key";
     pub const ACCESS_KEY: &'static str = "access_key";
     pub const RAM_ROLE_NAME: &'static str = "ram_role_name";
+    pub const RAM_METADATA_URL: &'static str = "ram_metadata_url";
+    pub const CREDENTIALS_PATH: &'static str = "credentials_path";
     pub const CONFIG_LONG_POLL_TIMEOUT: &'static str = "config_long_poll_timeout";
     pub const CONFIG_RETRY_TIME: &'static str = "config_retry_time";
     pub const MAX_RETRY: &'static str = "max_retry";
@@ -30,8 +32,14 @@ pub mod val {
     pub const CONFIG_LONG_POLL_TIMEOUT: i32 = 30000;
     pub const MIN_CONFIG_LONG_POLL_TIMEOUT: i32 = 10000;
     pub const CONFIG_RETRY_TIME: i32 = 1000;
+    pub const MAX_RETRY: i32 = 3;
     pub const DEFAULT_NAMESPACE: &'static str = "";
     pub const DEFAULT_GROUP: &'static str = "DEFAULT_GROUP";
+    pub const DEFAULT_ENDPOINT_PORT: &'static str = "8080";
+    pub const DEFAULT_CONTEXT_PATH: &'static str = "nacos";
+    pub const SERVER_LIST_NAME: &'static str = "serverlist";
+    pub const RAM_METADATA_URL: &'static str =
+        "http://100.100.100.200/latest/meta-data/ram/security-credentials/";
 }
 pub mod res_names {
     pub const RESP_ACCESS_TOKEN: &'static str = "accessToken";
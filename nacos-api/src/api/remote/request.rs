@@ -114,6 +114,14 @@ pub struct ServerCheckRequest {
     pub inner: RpcRequest,
 }
 
+impl HealthCheckRequest {
+    pub fn new() -> Self {
+        HealthCheckRequest {
+            inner: RpcRequest::default(),
+        }
+    }
+}
+
 impl ServerCheckRequest {
     pub fn new() -> Self {
         let request = RpcRequest::default();
@@ -195,11 +203,95 @@ impl Default for ConfigBatchListenRequest {
     }
 }
 
-impl RequestExt for RpcRequest {
-    fn ty_name(&self) -> String {
-        "RpcRequest".to_string()
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigQueryRequest {
+    #[serde(flatten)]
+    pub inner: RpcRequest,
+    pub data_id: String,
+    pub group: String,
+    pub tenant: Option<String>,
+    pub tag: Option<String>,
+}
+
+impl ConfigQueryRequest {
+    pub fn new(data_id: String, group: String, tenant: Option<String>) -> Self {
+        ConfigQueryRequest {
+            inner: Default::default(),
+            data_id,
+            group,
+            tenant,
+            tag: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigPublishRequest {
+    #[serde(flatten)]
+    pub inner: RpcRequest,
+    pub data_id: String,
+    pub group: String,
+    pub tenant: Option<String>,
+    pub content: String,
+    pub cas_md5: Option<String>,
+    pub addition_map: HashMap<String, String>,
+}
+
+impl ConfigPublishRequest {
+    pub fn new(data_id: String, group: String, tenant: Option<String>, content: String) -> Self {
+        ConfigPublishRequest {
+            inner: Default::default(),
+            data_id,
+            group,
+            tenant,
+            content,
+            cas_md5: None,
+            addition_map: HashMap::new(),
+        }
     }
 
+    pub fn get_addition_param(&self, key: &str) -> Option<&String> {
+        self.addition_map.get(key)
+    }
+
+    pub fn put_addition_param(&mut self, key: &str, value: String) {
+        self.addition_map.insert(key.to_string(), value);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigRemoveRequest {
+    #[serde(flatten)]
+    pub inner: RpcRequest,
+    pub data_id: String,
+    pub group: String,
+    pub tenant: Option<String>,
+    pub tag: Option<String>,
+}
+
+impl ConfigRemoveRequest {
+    pub fn new(
+        data_id: String,
+        group: String,
+        tenant: Option<String>,
+        tag: Option<String>,
+    ) -> Self {
+        ConfigRemoveRequest {
+            inner: Default::default(),
+            data_id,
+            group,
+            tenant,
+            tag,
+        }
+    }
+}
+
+impl RequestExt for RpcRequest {
+    const TYPE_NAME: &'static str = "RpcRequest";
+
     fn headers(&self) -> HashMap<String, String> {
         self.headers.clone()
     }
@@ -226,12 +318,66 @@ impl_server_request!(
 impl_config_request! {
     ConfigBatchListenRequest,
     ConfigChangeNotifyRequest,
+    ConfigQueryRequest,
+    ConfigPublishRequest,
+    ConfigRemoveRequest,
 }
 
 impl_req_ext! {
+    ClientDetectionRequest,
+    ConnectResetRequest,
     ServerCheckRequest,
     HealthCheckRequest,
     ConnectionSetupRequest,
     ConfigChangeNotifyRequest,
     ConfigBatchListenRequest,
+    ConfigQueryRequest,
+    ConfigPublishRequest,
+    ConfigRemoveRequest,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_config_publish_request_json() {
+        let mut request = ConfigPublishRequest::new(
+            "app.properties".to_string(),
+            "DEFAULT_GROUP".to_string(),
+            Some("dev".to_string()),
+            "k=v".to_string(),
+        );
+        request.cas_md5 = Some("md5".to_string());
+        request.put_addition_param("type", "properties".to_string());
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "headers": {},
+                "requestId": null,
+                "dataId": "app.properties",
+                "group": "DEFAULT_GROUP",
+                "tenant": "dev",
+                "content": "k=v",
+                "casMd5": "md5",
+                "additionMap": {"type": "properties"}
+            })
+        );
+    }
+
+    #[test]
+    fn test_config_remove_request_json() {
+        let request = ConfigRemoveRequest::new(
+            "app.properties".to_string(),
+            "DEFAULT_GROUP".to_string(),
+            None,
+            Some("beta".to_string()),
+        );
+        let value: Value = serde_json::to_value(&request).unwrap();
+        assert_eq!(value["dataId"], "app.properties");
+        assert_eq!(value["tag"], "beta");
+        assert_eq!(value["tenant"], Value::Null);
+    }
 }
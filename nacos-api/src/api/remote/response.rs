@@ -46,6 +46,24 @@ pub struct RpcResponse {
     pub request_id: Option<String>,
 }
 
+impl Default for RpcResponse {
+    fn default() -> Self {
+        RpcResponse {
+            result_code: CODE_SUCCESS,
+            error_code: 0,
+            message: None,
+            request_id: None,
+        }
+    }
+}
+
+impl RpcResponse {
+    /// Check if the server processed the request successfully.
+    pub fn is_success(&self) -> bool {
+        self.result_code == CODE_SUCCESS
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ClientDetectionResponse {
@@ -110,6 +128,81 @@ pub struct ServerReloadResponse {
     pub inner: RpcResponse,
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigQueryResponse {
+    #[serde(flatten)]
+    pub inner: RpcResponse,
+    pub content: Option<String>,
+    pub encrypted_data_key: Option<String>,
+    pub content_type: Option<String>,
+    pub md5: Option<String>,
+    #[serde(default)]
+    pub last_modified: i64,
+    #[serde(default)]
+    pub beta: bool,
+    pub tag: Option<String>,
+}
+
+impl ConfigQueryResponse {
+    /// error code returned when the config does not exist on server.
+    pub const CONFIG_NOT_FOUND: u32 = 300;
+    /// error code returned when the config is being modified.
+    pub const CONFIG_QUERY_CONFLICT: u32 = 400;
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigPublishResponse {
+    #[serde(flatten)]
+    pub inner: RpcResponse,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigRemoveResponse {
+    #[serde(flatten)]
+    pub inner: RpcResponse,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigContext {
+    pub group: String,
+    pub data_id: String,
+    pub tenant: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChangeBatchListenResponse {
+    #[serde(flatten)]
+    pub inner: RpcResponse,
+    #[serde(default)]
+    pub changed_configs: Vec<ConfigContext>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChangeNotifyResponse {
+    #[serde(flatten)]
+    pub inner: RpcResponse,
+}
+
+impl ConfigChangeNotifyResponse {
+    /// build a success response for the server push with `request_id`.
+    pub fn new(request_id: Option<String>) -> Self {
+        ConfigChangeNotifyResponse {
+            inner: RpcResponse {
+                result_code: CODE_SUCCESS,
+                error_code: 0,
+                message: None,
+                request_id,
+            },
+        }
+    }
+}
+
 impl_resp_ext! {
     ClientDetectionResponse,
     ConnectResetResponse,
@@ -119,4 +212,36 @@ impl_resp_ext! {
     ServerLoaderInfoResponse,
     ServerReloadResponse,
     Response,
+    ConfigQueryResponse,
+    ConfigPublishResponse,
+    ConfigRemoveResponse,
+    ConfigChangeBatchListenResponse,
+    ConfigChangeNotifyResponse,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_batch_listen_response() {
+        let json = r#"{"resultCode":200,"errorCode":0,"message":null,"requestId":"1",
+            "changedConfigs":[{"group":"DEFAULT_GROUP","dataId":"app","tenant":""}],
+            "success":true}"#;
+        let response = serde_json::from_str::<ConfigChangeBatchListenResponse>(json).unwrap();
+        assert!(response.is_success());
+        assert_eq!(response.changed_configs.len(), 1);
+        assert_eq!(response.changed_configs[0].data_id, "app");
+    }
+
+    #[test]
+    fn test_parse_query_response() {
+        let json = r#"{"resultCode":200,"errorCode":0,"message":null,"requestId":null,
+            "content":"k=v","encryptedDataKey":null,"contentType":"properties",
+            "md5":"abc","lastModified":1644480000000,"tag":null,"beta":false,"success":true}"#;
+        let response = serde_json::from_str::<ConfigQueryResponse>(json).unwrap();
+        assert_eq!(response.content.as_deref(), Some("k=v"));
+        assert_eq!(response.content_type.as_deref(), Some("properties"));
+        assert_eq!(response.last_modified, 1644480000000);
+    }
 }
@@ -6,7 +6,11 @@ use std::collections::HashMap;
 
 /// Rpc Request Extensions.
 pub trait RequestExt: Serialize + DeserializeOwned {
-    fn ty_name(&self) -> String;
+    /// type name of the request on the wire, e.g. `ConfigQueryRequest`.
+    const TYPE_NAME: &'static str;
+    fn ty_name(&self) -> String {
+        Self::TYPE_NAME.to_string()
+    }
     fn headers(&self) -> HashMap<String, String>;
     fn clear_headers(&mut self);
     fn to_string(&self) -> String {
@@ -18,7 +22,11 @@ pub trait RequestExt: Serialize + DeserializeOwned {
 }
 /// Rpc Response Extensions.
 pub trait ResponseExt: Serialize + DeserializeOwned {
-    fn ty_name(&self) -> String;
+    /// type name of the response on the wire, e.g. `ConfigQueryResponse`.
+    const TYPE_NAME: &'static str;
+    fn ty_name(&self) -> String {
+        Self::TYPE_NAME.to_string()
+    }
     fn to_string(&self) -> String {
         serde_json::to_string(&self).unwrap()
     }
@@ -53,9 +53,7 @@ macro_rules! impl_req_ext {
             }
 
         impl crate::api::traits::RequestExt for $target {
-                fn ty_name(&self) -> String {
-                    String::from(stringify!($target))
-                }
+                const TYPE_NAME: &'static str = stringify!($target);
 
                 fn headers(&self) -> HashMap<String, String> {
                     self.inner.headers()
@@ -88,9 +86,7 @@ macro_rules! impl_resp_ext {
                     }
                 }
             impl crate::api::traits::ResponseExt for $target {
-                    fn ty_name(&self) -> String {
-                        String::from(stringify!($target))
-                    }
+                    const TYPE_NAME: &'static str = stringify!($target);
             }
         )+
     };
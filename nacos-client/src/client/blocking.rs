@@ -0,0 +1,156 @@
+//! A blocking facade of [ConfigService] for sync codebases.
+use crate::client::service::{ConfigService, NacosConfigService, PublishConfigOptions};
+use crate::listeners::{ConfigInfo, ListenerHandle};
+use crate::Properties;
+use nacos_core::error::NacosResult;
+use tokio::runtime::Runtime;
+
+/// A [NacosConfigService] driven by its own tokio runtime.
+///
+/// Must not be used inside an async context, as it blocks the current thread on every call.
+pub struct BlockingConfigService {
+    inner: NacosConfigService,
+    runtime: Runtime,
+}
+
+impl BlockingConfigService {
+    /// create a [BlockingConfigService] and connect to the nacos server.
+    pub fn new(properties: Properties) -> NacosResult<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .thread_name("nacos-client-blocking")
+            .enable_all()
+            .build()?;
+        let inner = runtime.block_on(NacosConfigService::new(properties))?;
+        Ok(BlockingConfigService { inner, runtime })
+    }
+
+    /// Get nacos config, see [ConfigService::get_config].
+    pub fn get_config(
+        &self,
+        data_id: String,
+        group: String,
+        timeout_ms: i64,
+    ) -> NacosResult<String> {
+        self.runtime
+            .block_on(self.inner.get_config(data_id, group, timeout_ms))
+    }
+
+    /// Get config and register listener, see [ConfigService::get_config_and_sign_listener].
+    pub fn get_config_and_sign_listener<L>(
+        &self,
+        data_id: String,
+        group: String,
+        timeout_ms: i64,
+        listener: L,
+    ) -> NacosResult<(String, ListenerHandle)>
+    where
+        L: Fn(ConfigInfo) + Send + Sync + 'static,
+    {
+        self.runtime.block_on(
+            self.inner
+                .get_config_and_sign_listener(data_id, group, timeout_ms, listener),
+        )
+    }
+
+    /// Add a listener to the configuration, see [ConfigService::add_listener].
+    pub fn add_listener<L>(
+        &self,
+        data_id: String,
+        group: String,
+        listener: L,
+    ) -> NacosResult<ListenerHandle>
+    where
+        L: Fn(ConfigInfo) + Send + Sync + 'static,
+    {
+        self.runtime
+            .block_on(self.inner.add_listener(data_id, group, listener))
+    }
+
+    /// Remove listener.
+    pub fn remove_listener(&self, handle: ListenerHandle) {
+        self.runtime.block_on(self.inner.remove_listener(handle))
+    }
+
+    /// Publish config.
+    pub fn publish_config(
+        &self,
+        data_id: String,
+        group: String,
+        content: String,
+    ) -> NacosResult<bool> {
+        self.runtime
+            .block_on(self.inner.publish_config(data_id, group, content))
+    }
+
+    /// Publish config cas.
+    pub fn publish_config_with_md5(
+        &self,
+        data_id: String,
+        group: String,
+        content: String,
+        md5: String,
+    ) -> NacosResult<bool> {
+        self.runtime.block_on(
+            self.inner
+                .publish_config_with_md5(data_id, group, content, md5),
+        )
+    }
+
+    /// Publish config cas with type.
+    pub fn publish_config_with_md5_ty(
+        &self,
+        data_id: String,
+        group: String,
+        content: String,
+        md5: String,
+        r#type: String,
+    ) -> NacosResult<bool> {
+        self.runtime.block_on(
+            self.inner
+                .publish_config_with_md5_ty(data_id, group, content, md5, r#type),
+        )
+    }
+
+    /// Publish config with the optional params of `options`, e.g. tag and beta ips.
+    pub fn publish_config_with_options(
+        &self,
+        data_id: String,
+        group: String,
+        content: String,
+        options: PublishConfigOptions,
+    ) -> NacosResult<bool> {
+        self.runtime.block_on(
+            self.inner
+                .publish_config_with_options(data_id, group, content, options),
+        )
+    }
+
+    /// Remove config.
+    pub fn remove_config(&self, data_id: String, group: String) -> NacosResult<bool> {
+        self.runtime
+            .block_on(self.inner.remove_config(data_id, group))
+    }
+
+    /// Remove config of the specified tag only.
+    pub fn remove_config_with_tag(
+        &self,
+        data_id: String,
+        group: String,
+        tag: String,
+    ) -> NacosResult<bool> {
+        self.runtime
+            .block_on(self.inner.remove_config_with_tag(data_id, group, tag))
+    }
+
+    /// Get Server status.
+    pub fn get_server_status(&self) -> String {
+        self.inner.get_server_status()
+    }
+
+    /// Shutdown the service and its runtime.
+    pub fn shutdown(self) {
+        let BlockingConfigService { inner, runtime } = self;
+        runtime.block_on(inner.shutdown());
+        runtime.shutdown_background();
+    }
+}
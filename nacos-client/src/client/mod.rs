@@ -1,5 +1,9 @@
+pub mod blocking;
 pub mod cli;
 pub mod conn;
 pub mod handlers;
+pub mod server_list;
 pub mod service;
 pub mod worker;
+
+pub use crate::listeners::{ConfigInfo, ListenerHandle};
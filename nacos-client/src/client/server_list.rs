@@ -0,0 +1,395 @@
+//! A module to manage the addresses of nacos servers.
+use crate::client::cli::{RetryPolicy, ServerListFactory};
+use crate::listeners::{ServerListChangedEvent, ServerListChangedListener};
+use crate::Properties;
+use nacos_api::api::consts::{names, val};
+use nacos_common::common::remote::client::cli::try_resolve_server_info;
+use nacos_core::error::{codes, NacosError, NacosResult};
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// A server is skipped in rotation after this count of consecutive failures,
+/// unless all servers have failed.
+const MAX_FAILURES: u32 = 3;
+/// a skipped server is tried again this long after its last failure.
+const FAILURE_PENALTY: Duration = Duration::from_secs(30);
+/// interval to refresh server list from address server.
+const REFRESH_SERVER_LIST_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Owns the server address list shared by grpc connection, http client and login,
+/// and hands out servers round-robin starting from a random offset.
+pub struct ServerListManager {
+    servers: RwLock<Vec<String>>,
+    index: AtomicUsize,
+    // consecutive failures of servers and when they failed lastly.
+    failures: Mutex<HashMap<String, (u32, Instant)>>,
+    failure_penalty: Duration,
+    // url of address server to fetch server list, e.g. `http://endpoint:8080/nacos/serverlist`.
+    endpoint_url: Option<String>,
+    listeners: RwLock<Vec<Box<dyn ServerListChangedListener>>>,
+}
+
+impl ServerListManager {
+    /// create a [ServerListManager] with addresses like `127.0.0.1:8848` or `http://127.0.0.1:8848`,
+    /// addresses are normalized to `ip:port`.
+    pub fn new(servers: Vec<String>) -> Self {
+        let servers = resolve_server_addrs(servers);
+        ServerListManager {
+            index: AtomicUsize::new(random_index(servers.len())),
+            servers: RwLock::new(servers),
+            failures: Mutex::new(HashMap::new()),
+            failure_penalty: FAILURE_PENALTY,
+            endpoint_url: None,
+            listeners: RwLock::new(vec![]),
+        }
+    }
+
+    /// create a [ServerListManager] fetching server list from the address server `endpoint`.
+    pub fn with_endpoint(endpoint: &str, port: &str, context_path: &str) -> Self {
+        let context_path = context_path.trim_matches('/');
+        let endpoint_url = if context_path.is_empty() {
+            format!("http://{}:{}/{}", endpoint, port, val::SERVER_LIST_NAME)
+        } else {
+            format!(
+                "http://{}:{}/{}/{}",
+                endpoint,
+                port,
+                context_path,
+                val::SERVER_LIST_NAME
+            )
+        };
+        let mut manager = ServerListManager::new(vec![]);
+        manager.endpoint_url = Some(endpoint_url);
+        manager
+    }
+
+    /// create a [ServerListManager] with comma separated addresses of `server_addr` property,
+    /// or with address server if `endpoint` is specified.
+    pub fn from_properties(properties: &Properties) -> Self {
+        if let Some(endpoint) = properties.get(names::ENDPOINT) {
+            if !endpoint.trim().is_empty() {
+                let port = properties
+                    .get(names::ENDPOINT_PORT)
+                    .map(String::as_str)
+                    .unwrap_or(val::DEFAULT_ENDPOINT_PORT);
+                let context_path = properties
+                    .get(names::CONTEXT_PATH)
+                    .map(String::as_str)
+                    .unwrap_or(val::DEFAULT_CONTEXT_PATH);
+                return ServerListManager::with_endpoint(endpoint.trim(), port, context_path);
+            }
+        }
+        let servers = match properties.get(names::SERVER_ADDR) {
+            Some(addrs) => parse_server_addrs(addrs),
+            None => vec![format!("127.0.0.1:{}", names::DEFAULT_PORT)],
+        };
+        ServerListManager::new(servers)
+    }
+
+    pub fn endpoint_url(&self) -> Option<&str> {
+        self.endpoint_url.as_deref()
+    }
+
+    /// register a listener notified when the server list changes.
+    pub fn add_listener<L>(&self, listener: L)
+    where
+        L: ServerListChangedListener + 'static,
+    {
+        let mut lock = self.listeners.write().unwrap();
+        lock.push(Box::new(listener));
+    }
+
+    /// replace the server list, returns `true` and emits [ServerListChangedEvent] if it changed.
+    pub fn update_server_list(&self, servers: Vec<String>) -> bool {
+        let servers = resolve_server_addrs(servers);
+        {
+            let mut current = self.servers.write().unwrap();
+            let old: HashSet<&String> = current.iter().collect();
+            let new: HashSet<&String> = servers.iter().collect();
+            if old == new {
+                return false;
+            }
+            // start from a random offset once servers are known, e.g. fetched from address server.
+            if current.is_empty() {
+                self.index
+                    .store(random_index(servers.len()), Ordering::SeqCst);
+            }
+            *current = servers.clone();
+            let mut failures = self.failures.lock().unwrap();
+            failures.retain(|server, _| new.contains(server));
+        }
+        info!("server list changed to {:?}", servers);
+        let event = ServerListChangedEvent { servers };
+        for listener in self.listeners.read().unwrap().iter() {
+            listener.on_changed(&event);
+        }
+        true
+    }
+
+    /// fetch server list from address server, returns `true` if it changed.
+    pub async fn refresh_server_list(&self) -> NacosResult<bool> {
+        let endpoint_url = self
+            .endpoint_url
+            .as_ref()
+            .ok_or(NacosError::client("no endpoint specified"))?;
+        let body = crate::http::get(endpoint_url.to_string()).await?;
+        let servers = resolve_server_addrs(
+            body.lines()
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty())
+                .collect(),
+        );
+        if servers.is_empty() {
+            return Err(NacosError::server(
+                codes::SERVER_ERROR,
+                format!("no valid server from address server {}", endpoint_url),
+            ));
+        }
+        Ok(self.update_server_list(servers))
+    }
+
+    /// refresh server list from address server periodically until the manager is dropped,
+    /// each refresh is retried by `retry_policy`.
+    pub fn start_refresh(self: &Arc<Self>, retry_policy: RetryPolicy) {
+        if self.endpoint_url.is_none() {
+            return;
+        }
+        let manager = Arc::downgrade(self);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(REFRESH_SERVER_LIST_INTERVAL).await;
+                let manager = match manager.upgrade() {
+                    Some(manager) => manager,
+                    None => break,
+                };
+                let refreshed = retry_policy.retry(|_| manager.refresh_server_list()).await;
+                if let Err(error) = refreshed {
+                    warn!("refresh server list error, {}", error);
+                }
+            }
+        });
+    }
+
+    pub fn server_list(&self) -> Vec<String> {
+        self.servers.read().unwrap().clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.servers.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// the server last handed out.
+    pub fn current_server(&self) -> Option<String> {
+        let servers = self.servers.read().unwrap();
+        if servers.is_empty() {
+            return None;
+        }
+        let index = self.index.load(Ordering::SeqCst);
+        Some(servers[index % servers.len()].clone())
+    }
+
+    /// the next server in rotation, servers failed too many times are skipped until
+    /// the penalty after their last failure passes.
+    pub fn next_server(&self) -> Option<String> {
+        let servers = self.servers.read().unwrap();
+        if servers.is_empty() {
+            return None;
+        }
+        let failures = self.failures.lock().unwrap();
+        let is_available = |server: &String| match failures.get(server) {
+            Some((count, last_failure)) => {
+                *count < MAX_FAILURES || last_failure.elapsed() >= self.failure_penalty
+            }
+            None => true,
+        };
+        let start = self.index.fetch_add(1, Ordering::SeqCst) + 1;
+        for offset in 0..servers.len() {
+            let server = &servers[(start + offset) % servers.len()];
+            if is_available(server) {
+                self.index.store(start + offset, Ordering::SeqCst);
+                return Some(server.clone());
+            }
+        }
+        // all servers failed, rotate anyway.
+        Some(servers[start % servers.len()].clone())
+    }
+
+    pub fn record_failure(&self, server: &str) {
+        let mut failures = self.failures.lock().unwrap();
+        let (count, last_failure) = failures
+            .entry(server.to_string())
+            .or_insert((0, Instant::now()));
+        *count += 1;
+        *last_failure = Instant::now();
+    }
+
+    pub fn record_success(&self, server: &str) {
+        self.failures.lock().unwrap().remove(server);
+    }
+
+    pub fn failure_count(&self, server: &str) -> u32 {
+        let failures = self.failures.lock().unwrap();
+        failures.get(server).map(|(count, _)| *count).unwrap_or(0)
+    }
+}
+
+impl ServerListFactory for ServerListManager {
+    fn next_server(&self) -> Option<String> {
+        ServerListManager::next_server(self)
+    }
+
+    fn server_list(&self) -> Vec<String> {
+        ServerListManager::server_list(self)
+    }
+
+    fn record_failure(&self, server: &str) {
+        ServerListManager::record_failure(self, server)
+    }
+
+    fn record_success(&self, server: &str) {
+        ServerListManager::record_success(self, server)
+    }
+}
+
+/// a random index in `0..len`, `0` if `len` is `0`.
+fn random_index(len: usize) -> usize {
+    if len == 0 {
+        0
+    } else {
+        rand::thread_rng().gen_range(0..len)
+    }
+}
+
+/// normalize addresses to `ip:port`, malformed ones are skipped.
+fn resolve_server_addrs(servers: Vec<String>) -> Vec<String> {
+    servers
+        .into_iter()
+        .filter_map(|server| match try_resolve_server_info(&server) {
+            Ok(server_info) => Some(server_info.address()),
+            Err(error) => {
+                warn!("skip server, {}", error);
+                None
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn parse_server_addrs(addrs: &str) -> Vec<String> {
+    addrs
+        .split(',')
+        .map(|addr| addr.trim().to_string())
+        .filter(|addr| !addr.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ServerListManager;
+    use crate::listeners::{ServerListChangedEvent, ServerListChangedListener};
+    use std::collections::HashSet;
+    use std::sync::{mpsc, Mutex};
+    use std::time::Duration;
+
+    #[test]
+    fn test_round_robin() {
+        let servers = vec![
+            "127.0.0.1:8848".to_string(),
+            "127.0.0.2:8848".to_string(),
+            "127.0.0.3:8848".to_string(),
+        ];
+        let manager = ServerListManager::new(servers.clone());
+        let first = manager.next_server().unwrap();
+        let second = manager.next_server().unwrap();
+        let third = manager.next_server().unwrap();
+        let handed: HashSet<String> = vec![first.clone(), second, third].into_iter().collect();
+        assert_eq!(handed.len(), 3);
+        assert_eq!(manager.next_server(), Some(first));
+    }
+
+    #[test]
+    fn test_skip_failed_server() {
+        let manager = ServerListManager::new(vec![
+            "127.0.0.1:8848".to_string(),
+            "127.0.0.2:8848".to_string(),
+        ]);
+        for _ in 0..3 {
+            manager.record_failure("127.0.0.1:8848");
+        }
+        for _ in 0..4 {
+            assert_eq!(manager.next_server().unwrap(), "127.0.0.2:8848");
+        }
+        manager.record_success("127.0.0.1:8848");
+        assert_eq!(manager.failure_count("127.0.0.1:8848"), 0);
+        let handed: HashSet<String> = (0..2).filter_map(|_| manager.next_server()).collect();
+        assert_eq!(handed.len(), 2);
+    }
+
+    #[test]
+    fn test_retry_failed_server_after_penalty() {
+        let mut manager = ServerListManager::new(vec![
+            "127.0.0.1:8848".to_string(),
+            "127.0.0.2:8848".to_string(),
+        ]);
+        manager.failure_penalty = Duration::from_millis(50);
+        for _ in 0..3 {
+            manager.record_failure("127.0.0.1:8848");
+        }
+        assert_eq!(manager.next_server().unwrap(), "127.0.0.2:8848");
+        assert_eq!(manager.next_server().unwrap(), "127.0.0.2:8848");
+        std::thread::sleep(Duration::from_millis(60));
+        let handed: HashSet<String> = (0..2).filter_map(|_| manager.next_server()).collect();
+        assert!(handed.contains("127.0.0.1:8848"));
+    }
+
+    #[test]
+    fn test_random_offset_of_fetched_servers() {
+        let servers = vec![
+            "127.0.0.1:8848".to_string(),
+            "127.0.0.2:8848".to_string(),
+            "127.0.0.3:8848".to_string(),
+        ];
+        let handed: HashSet<String> = (0..30)
+            .filter_map(|_| {
+                let manager = ServerListManager::with_endpoint("127.0.0.1", "8080", "nacos");
+                manager.update_server_list(servers.clone());
+                manager.next_server()
+            })
+            .collect();
+        assert!(handed.len() > 1);
+    }
+
+    struct ChannelListener(Mutex<mpsc::Sender<ServerListChangedEvent>>);
+
+    impl ServerListChangedListener for ChannelListener {
+        fn on_changed(&self, event: &ServerListChangedEvent) {
+            self.0.lock().unwrap().send(event.clone()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_refresh_from_endpoint() {
+        // malformed lines are skipped.
+        let port = crate::http::stub::serve("10.0.0.1:8848\n10.0.0.2\n10.0.0.3:abc\n");
+        let manager = ServerListManager::with_endpoint("127.0.0.1", &port.to_string(), "/nacos");
+        assert_eq!(
+            manager.endpoint_url(),
+            Some(format!("http://127.0.0.1:{}/nacos/serverlist", port).as_str())
+        );
+        let (sender, receiver) = mpsc::channel();
+        manager.add_listener(ChannelListener(Mutex::new(sender)));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        assert!(runtime.block_on(manager.refresh_server_list()).unwrap());
+        let event = receiver.try_recv().unwrap();
+        assert_eq!(event.servers, vec!["10.0.0.1:8848", "10.0.0.2:8848"]);
+        // unchanged list emits nothing.
+        assert!(!runtime.block_on(manager.refresh_server_list()).unwrap());
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -1,6 +1,16 @@
+use crate::client::cli::timeout_mills;
+use crate::client::handlers::server::ServerRequestHandler;
+use crate::client::worker::tenant::get_user_tenant_for_acm;
 use crate::client::worker::ClientWorker;
+use crate::common::require_nonnull;
+use crate::config::ty::ConfigType;
+use crate::listeners::{ConfigInfo, ListenerHandle};
+use crate::Properties;
+use nacos_api::api::consts::names;
 use nacos_core::error::NacosResult;
+use std::sync::Arc;
 
+#[tonic::async_trait]
 pub trait ConfigService {
     /// Get nacos config
     /// # Params
@@ -9,31 +19,46 @@ pub trait ConfigService {
     /// * timeout_ms - read timeout
     /// # Returns
     /// * config value
-    fn get_config(&self, data_id: String, group: String, timeout_ms: i64) -> NacosResult<String>;
+    async fn get_config(
+        &self,
+        data_id: String,
+        group: String,
+        timeout_ms: i64,
+    ) -> NacosResult<String>;
 
-    /// Get config and register listener
-    fn get_config_and_sign_listener(
-        &mut self,
+    /// Get config and register listener, the listener is removed when the returned handle is dropped.
+    async fn get_config_and_sign_listener<L>(
+        &self,
         data_id: String,
         group: String,
         timeout_ms: i64,
-        listener: fn(String) -> (),
-    );
+        listener: L,
+    ) -> NacosResult<(String, ListenerHandle)>
+    where
+        L: Fn(ConfigInfo) + Send + Sync + 'static;
 
     /// Add a listener to the configuration, after the server modified the configuration, the client will use the
     /// incoming listener callback.
-    fn add_listener(
-        &mut self,
+    /// The listener is removed when the returned handle is dropped or unsubscribed.
+    async fn add_listener<L>(
+        &self,
         data_id: String,
         group: String,
-        listener: fn(String) -> (),
-    ) -> NacosResult<()>;
+        listener: L,
+    ) -> NacosResult<ListenerHandle>
+    where
+        L: Fn(ConfigInfo) + Send + Sync + 'static;
 
     /// Publish config.
-    fn publish_config(&self, data_id: String, group: String, content: String) -> NacosResult<bool>;
+    async fn publish_config(
+        &self,
+        data_id: String,
+        group: String,
+        content: String,
+    ) -> NacosResult<bool>;
 
     /// Publish config cas.
-    fn publish_config_with_md5(
+    async fn publish_config_with_md5(
         &self,
         data_id: String,
         group: String,
@@ -42,7 +67,7 @@ pub trait ConfigService {
     ) -> NacosResult<bool>;
 
     /// Publish config cas with type.
-    fn publish_config_with_md5_ty(
+    async fn publish_config_with_md5_ty(
         &self,
         data_id: String,
         group: String,
@@ -51,28 +76,291 @@ pub trait ConfigService {
         r#type: String,
     ) -> NacosResult<bool>;
 
+    /// Publish config with the optional params of `options`, e.g. tag and beta ips.
+    async fn publish_config_with_options(
+        &self,
+        data_id: String,
+        group: String,
+        content: String,
+        options: PublishConfigOptions,
+    ) -> NacosResult<bool>;
+
     /// Remove config.
-    fn remove_config(&mut self, data_id: String, group: String) -> NacosResult<bool>;
+    async fn remove_config(&self, data_id: String, group: String) -> NacosResult<bool>;
+
+    /// Remove config of the specified tag only.
+    async fn remove_config_with_tag(
+        &self,
+        data_id: String,
+        group: String,
+        tag: String,
+    ) -> NacosResult<bool>;
 
     /// Remove listener.
-    fn remove_listener(&mut self, data_id: String, group: String, listener: &fn(String) -> ());
+    async fn remove_listener(&self, handle: ListenerHandle);
 
     /// Get Server status.
     fn get_server_status(&self) -> String;
 
     /// Shutdown the resource service
-    fn shutdown(self);
+    async fn shutdown(self);
+}
+
+/// Optional params of [ConfigService::publish_config_with_options].
+#[derive(Debug, Clone, Default)]
+pub struct PublishConfigOptions {
+    /// md5 of the config on server, makes it a cas publish.
+    pub cas_md5: Option<String>,
+    /// type of config, `text` if not specified.
+    pub ty: Option<String>,
+    /// tag of the config variant to publish.
+    pub tag: Option<String>,
+    /// name of the publishing app, `APP_NAME` env if not specified.
+    pub app_name: Option<String>,
+    /// comma separated client ips to publish a beta config to.
+    pub beta_ips: Option<String>,
 }
 
 pub(crate) type ConfigFilterChainManager = fn(String) -> String;
 pub struct NacosConfigService {
-    pub(crate) worker: ClientWorker,
+    pub(crate) worker: Arc<ClientWorker>,
     pub(crate) namespace: String,
     /// non used here
     pub(crate) filter_chain: ConfigFilterChainManager,
 }
 
+fn default_filter_chain(content: String) -> String {
+    content
+}
+
+/// read timeout of query, the default timeout is used if `timeout_ms` is not positive.
+fn read_timeout(timeout_ms: i64) -> u64 {
+    if timeout_ms > 0 {
+        timeout_ms as u64
+    } else {
+        timeout_mills()
+    }
+}
+
 impl NacosConfigService {
     const UP: &'static str = "UP";
     const DOWN: &'static str = "DOWN";
+    const APP_NAME: &'static str = "APP_NAME";
+
+    /// create a [NacosConfigService] and connect to the nacos server.
+    pub async fn new(properties: Properties) -> NacosResult<Self> {
+        let namespace = properties
+            .get(names::NAMESPACE)
+            .map(|namespace| namespace.trim().to_string())
+            .unwrap_or_else(get_user_tenant_for_acm);
+        let filter_chain: ConfigFilterChainManager = default_filter_chain;
+        let mut worker = ClientWorker::new(filter_chain, properties);
+        worker.start(Some(namespace.clone())).await?;
+        let worker = Arc::new(worker);
+        worker.start_config_listen();
+        Ok(NacosConfigService {
+            worker,
+            namespace,
+            filter_chain,
+        })
+    }
+
+    /// register a handler of requests pushed by server.
+    pub fn register_server_request_handler<H>(&self, handler: H)
+    where
+        H: ServerRequestHandler,
+    {
+        if let Some(rpc_client) = self.worker.rpc_client.as_ref() {
+            rpc_client.register_server_request_handler(handler);
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl ConfigService for NacosConfigService {
+    async fn get_config(
+        &self,
+        data_id: String,
+        group: String,
+        timeout_ms: i64,
+    ) -> NacosResult<String> {
+        require_nonnull("data_id", data_id.as_str())?;
+        let response = self
+            .worker
+            .get_server_config(
+                data_id.as_str(),
+                group.as_str(),
+                self.namespace.as_str(),
+                read_timeout(timeout_ms),
+                false,
+            )
+            .await?;
+        let content = response.content.unwrap_or_default();
+        Ok((self.filter_chain)(content))
+    }
+
+    async fn get_config_and_sign_listener<L>(
+        &self,
+        data_id: String,
+        group: String,
+        timeout_ms: i64,
+        listener: L,
+    ) -> NacosResult<(String, ListenerHandle)>
+    where
+        L: Fn(ConfigInfo) + Send + Sync + 'static,
+    {
+        require_nonnull("data_id", data_id.as_str())?;
+        let response = self
+            .worker
+            .get_server_config(
+                data_id.as_str(),
+                group.as_str(),
+                self.namespace.as_str(),
+                read_timeout(timeout_ms),
+                false,
+            )
+            .await?;
+        let handle = self.worker.add_tenant_listener(
+            data_id.as_str(),
+            group.as_str(),
+            self.namespace.as_str(),
+            Some(&response),
+            listener,
+        )?;
+        let content = response.content.unwrap_or_default();
+        Ok(((self.filter_chain)(content), handle))
+    }
+
+    async fn add_listener<L>(
+        &self,
+        data_id: String,
+        group: String,
+        listener: L,
+    ) -> NacosResult<ListenerHandle>
+    where
+        L: Fn(ConfigInfo) + Send + Sync + 'static,
+    {
+        require_nonnull("data_id", data_id.as_str())?;
+        self.worker.add_tenant_listener(
+            data_id.as_str(),
+            group.as_str(),
+            self.namespace.as_str(),
+            None,
+            listener,
+        )
+    }
+
+    async fn publish_config(
+        &self,
+        data_id: String,
+        group: String,
+        content: String,
+    ) -> NacosResult<bool> {
+        self.publish_config_with_md5_ty(
+            data_id,
+            group,
+            content,
+            "".to_string(),
+            ConfigType::TEXT.to_string(),
+        )
+        .await
+    }
+
+    async fn publish_config_with_md5(
+        &self,
+        data_id: String,
+        group: String,
+        content: String,
+        md5: String,
+    ) -> NacosResult<bool> {
+        self.publish_config_with_md5_ty(data_id, group, content, md5, ConfigType::TEXT.to_string())
+            .await
+    }
+
+    async fn publish_config_with_md5_ty(
+        &self,
+        data_id: String,
+        group: String,
+        content: String,
+        md5: String,
+        r#type: String,
+    ) -> NacosResult<bool> {
+        let options = PublishConfigOptions {
+            cas_md5: Some(md5),
+            ty: Some(r#type),
+            ..Default::default()
+        };
+        self.publish_config_with_options(data_id, group, content, options)
+            .await
+    }
+
+    async fn publish_config_with_options(
+        &self,
+        data_id: String,
+        group: String,
+        content: String,
+        options: PublishConfigOptions,
+    ) -> NacosResult<bool> {
+        require_nonnull("data_id", data_id.as_str())?;
+        require_nonnull("content", content.as_str())?;
+        let ty = options
+            .ty
+            .filter(|ty| !ty.trim().is_empty())
+            .unwrap_or_else(|| ConfigType::TEXT.to_string());
+        let app_name = options
+            .app_name
+            .or_else(|| std::env::var(Self::APP_NAME).ok());
+        self.worker
+            .publish_config(
+                data_id.as_str(),
+                group.as_str(),
+                self.namespace.as_str(),
+                app_name,
+                options.tag.filter(|tag| !tag.trim().is_empty()),
+                options.beta_ips.filter(|ips| !ips.trim().is_empty()),
+                content.as_str(),
+                None,
+                options.cas_md5,
+                ty.as_str(),
+            )
+            .await
+    }
+
+    async fn remove_config(&self, data_id: String, group: String) -> NacosResult<bool> {
+        self.remove_config_with_tag(data_id, group, "".to_string())
+            .await
+    }
+
+    async fn remove_config_with_tag(
+        &self,
+        data_id: String,
+        group: String,
+        tag: String,
+    ) -> NacosResult<bool> {
+        require_nonnull("data_id", data_id.as_str())?;
+        self.worker
+            .remove_config(
+                data_id.as_str(),
+                group.as_str(),
+                self.namespace.as_str(),
+                Some(tag),
+            )
+            .await
+    }
+
+    async fn remove_listener(&self, handle: ListenerHandle) {
+        handle.unsubscribe();
+    }
+
+    fn get_server_status(&self) -> String {
+        if self.worker.is_health_server() {
+            Self::UP.to_string()
+        } else {
+            Self::DOWN.to_string()
+        }
+    }
+
+    async fn shutdown(self) {
+        self.worker.shutdown();
+    }
 }
@@ -1,12 +1,42 @@
+use crate::client::cli::{timeout_mills, ClientState, RetryPolicy, RpcClient};
+use crate::client::handlers::server::ServerRequestHandler;
+use crate::client::server_list::ServerListManager;
 use crate::client::service::ConfigFilterChainManager;
 use crate::common::GroupKey;
 use crate::config::cache::CacheData;
+use crate::credentials::{self, CredentialsProvider, StaticCredentialsProvider};
+use crate::listeners::{
+    ConfigInfo, ConnectionEventListener, ListenerHandle, ServerListChangedEvent,
+    ServerListChangedListener,
+};
+use crate::security::{self, SecurityHeaderProvider, SecurityProxy};
+use crate::spas::{SignModule, SpasSigner};
 use lazy_static::lazy_static;
+use nacos_api::api::ability::env::create_config_labels;
 use nacos_api::api::consts::{names, val};
+use nacos_api::api::remote::request::{
+    ConfigBatchListenRequest, ConfigChangeNotifyRequest, ConfigListenContext, ConfigPublishRequest,
+    ConfigQueryRequest, ConfigRemoveRequest,
+};
+use nacos_api::api::remote::response::{
+    ConfigChangeBatchListenResponse, ConfigChangeNotifyResponse, ConfigPublishResponse,
+    ConfigQueryResponse, ConfigRemoveResponse,
+};
+use nacos_api::api::traits::{RequestExt, ResponseExt};
+use nacos_core::error::{codes, CasMismatchError, NacosError, NacosResult};
 use std::cmp::max;
-use std::collections::HashMap;
-use std::sync::atomic::AtomicBool;
-use std::sync::{Arc, RwLock};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// max count of configs in one listen task.
+const PER_TASK_CONFIG_SIZE: usize = 3000;
+/// interval of listen rounds if nothing triggers the listen.
+const LISTEN_INTERVAL: Duration = Duration::from_secs(5);
+/// interval to re-listen all configs including those already synced with server.
+const ALL_SYNC_INTERVAL: Duration = Duration::from_secs(5 * 60);
 
 lazy_static! {
     static ref CACHE_MAP: RwLock<HashMap<String, Arc<CacheData>>> = RwLock::new(HashMap::new());
@@ -14,21 +44,38 @@ lazy_static! {
 
 pub(crate) struct ClientWorker {
     // ConfigFilterChainManager todo.
-    pub(crate) is_health_server: AtomicBool,
     pub(crate) config_filter_chain_manager: Option<ConfigFilterChainManager>,
     pub(crate) timeout: i32,
-    pub(crate) task_penalty_time: i32,
+    pub(crate) retry_policy: RetryPolicy,
     pub(crate) enable_remote_sync_config: bool,
+    pub(crate) server_list: Arc<ServerListManager>,
+    pub(crate) rpc_client: Option<Arc<RpcClient>>,
+    pub(crate) security_proxy: Arc<Mutex<SecurityProxy>>,
+    // credentials for both login and request signing.
+    pub(crate) credentials_provider: Arc<dyn CredentialsProvider>,
+    pub(crate) tenant: String,
+    pub(crate) is_shutdown: AtomicBool,
+    // bell to trigger a listen round immediately.
+    listen_bell: mpsc::Sender<()>,
+    listen_bell_receiver: Mutex<Option<mpsc::Receiver<()>>>,
 }
 
 impl Default for ClientWorker {
     fn default() -> Self {
+        let (listen_bell, listen_bell_receiver) = mpsc::channel(1);
         ClientWorker {
-            is_health_server: Default::default(),
             config_filter_chain_manager: None,
             timeout: 0,
-            task_penalty_time: 0,
+            retry_policy: Default::default(),
             enable_remote_sync_config: false,
+            server_list: Arc::new(ServerListManager::new(vec![])),
+            rpc_client: None,
+            security_proxy: Default::default(),
+            credentials_provider: Arc::new(StaticCredentialsProvider(Default::default())),
+            tenant: val::DEFAULT_NAMESPACE.to_string(),
+            is_shutdown: AtomicBool::new(false),
+            listen_bell,
+            listen_bell_receiver: Mutex::new(Some(listen_bell_receiver)),
         }
     }
 }
@@ -41,6 +88,8 @@ impl ClientWorker {
     const TYPE_PARAM: &'static str = "type";
     const ENCRYPTED_DATA_KEY_PARAM: &'static str = "encryptedDataKey";
     const DEFAULT_RESOURCE: &'static str = "";
+    /// message of the failed response to a cas publish whose md5 mismatches, in lower case.
+    const CAS_MISMATCH_MESSAGE: &'static str = "cas publish fail";
 
     fn init(&mut self, properties: &HashMap<String, String>) {
         let timeout = if let Some(timeout) = properties.get(names::CONFIG_LONG_POLL_TIMEOUT) {
@@ -50,12 +99,10 @@ impl ClientWorker {
         };
         let timeout = max(timeout, val::MIN_CONFIG_LONG_POLL_TIMEOUT);
         self.timeout = timeout;
-        let task_penalty_time = if let Some(time) = properties.get(names::CONFIG_RETRY_TIME) {
-            time.parse().unwrap()
-        } else {
-            val::CONFIG_RETRY_TIME
-        };
-        self.task_penalty_time = task_penalty_time;
+        self.retry_policy = retry_policy(
+            properties.get(names::MAX_RETRY).map(String::as_str),
+            properties.get(names::CONFIG_RETRY_TIME).map(String::as_str),
+        );
         let enable_remote_sync_config =
             if let Some(config) = properties.get(names::ENABLE_REMOTE_SYNC_CONFIG) {
                 config.parse().unwrap()
@@ -63,6 +110,17 @@ impl ClientWorker {
                 false
             };
         self.enable_remote_sync_config = enable_remote_sync_config;
+        self.server_list = Arc::new(ServerListManager::from_properties(properties));
+        self.credentials_provider = credentials::from_properties(properties);
+        let context_path = properties
+            .get(names::CONTEXT_PATH)
+            .map(String::as_str)
+            .unwrap_or(val::DEFAULT_CONTEXT_PATH);
+        let context_path = format!("/{}", context_path.trim_matches('/'));
+        self.security_proxy = Arc::new(Mutex::new(SecurityProxy::with_provider(
+            Some(self.credentials_provider.clone()),
+            context_path.as_str(),
+        )));
     }
 
     pub fn new(
@@ -78,9 +136,531 @@ impl ClientWorker {
         client_worker
     }
 
-    fn add_listeners(&mut self, data_id: String, group: String, listeners: Vec<fn(String) -> ()>) {
-        let group = blank2_default_group(group);
-        let cache = add_cache_data_if_absent(data_id, group);
+    /// connect to the first available server in server list.
+    pub(crate) async fn start(&mut self, tenant: Option<String>) -> NacosResult<()> {
+        self.tenant = tenant.clone().unwrap_or_default();
+        let mut rpc_client = RpcClient::new(tenant, create_config_labels());
+        rpc_client.retry_policy = self.retry_policy.clone();
+        let rpc_client = Arc::new(rpc_client);
+        rpc_client.register_server_request_handler(ConfigChangeNotifyHandler {
+            listen_bell: self.listen_bell.clone(),
+        });
+        rpc_client.register_connection_event_listener(ConfigConnectionEventListener {
+            listen_bell: self.listen_bell.clone(),
+        });
+        if let Some(endpoint_url) = self.server_list.endpoint_url() {
+            info!("fetch server list from address server {}", endpoint_url);
+            self.retry_policy
+                .retry(|_| self.server_list.refresh_server_list())
+                .await?;
+            self.server_list.start_refresh(self.retry_policy.clone());
+        }
+        if let Some(interval) = self.credentials_provider.refresh_interval() {
+            if let Err(error) = self.credentials_provider.refresh().await {
+                warn!(
+                    "fetch credentials failed, retry in {:?}, {}",
+                    interval, error
+                );
+            }
+            credentials::start_refresh(&self.credentials_provider);
+        }
+        if self.security_proxy.lock().unwrap().is_enabled() {
+            // requests are rejected without token, but the token may be ready in later refresh.
+            if let Err(error) = security::refresh_login(
+                self.security_proxy.clone(),
+                &self.server_list,
+                &self.retry_policy,
+            )
+            .await
+            {
+                warn!("login failed, {}", error);
+            }
+            security::start_refresh(
+                &self.security_proxy,
+                self.server_list.clone(),
+                self.retry_policy.clone(),
+            );
+            rpc_client
+                .register_header_provider(SecurityHeaderProvider(self.security_proxy.clone()));
+        }
+        rpc_client.register_header_provider(SpasSigner::with_provider(
+            self.credentials_provider.clone(),
+            SignModule::Config,
+        ));
+        self.server_list
+            .add_listener(ServerListWatcher(Arc::downgrade(&rpc_client)));
+        rpc_client.start(self.server_list.clone()).await?;
+        self.rpc_client = Some(rpc_client);
+        Ok(())
+    }
+
+    fn rpc_client(&self) -> NacosResult<&Arc<RpcClient>> {
+        self.rpc_client
+            .as_ref()
+            .ok_or(NacosError::client("client worker is not started"))
+    }
+
+    /// send request by [RpcClient::request], logs in again once if the request is forbidden,
+    /// e.g. the token expires before refreshed.
+    async fn request<Req, Resp>(&self, request: &Req, timeout_millis: u64) -> NacosResult<Resp>
+    where
+        Req: RequestExt,
+        Resp: ResponseExt + 'static,
+    {
+        self.request_with_policy(request, timeout_millis, &self.retry_policy)
+            .await
+    }
+
+    /// send request like [ClientWorker::request] but retried by `retry_policy`.
+    async fn request_with_policy<Req, Resp>(
+        &self,
+        request: &Req,
+        timeout_millis: u64,
+        retry_policy: &RetryPolicy,
+    ) -> NacosResult<Resp>
+    where
+        Req: RequestExt,
+        Resp: ResponseExt + 'static,
+    {
+        let rpc_client = self.rpc_client()?;
+        match rpc_client
+            .request_with_policy(request, timeout_millis, retry_policy)
+            .await
+        {
+            Err(NacosError::Forbidden(message))
+                if self.security_proxy.lock().unwrap().is_enabled() =>
+            {
+                warn!("request is forbidden, login again, {}", message);
+                self.security_proxy.lock().unwrap().expire();
+                security::refresh_login(
+                    self.security_proxy.clone(),
+                    &self.server_list,
+                    &self.retry_policy,
+                )
+                .await?;
+                rpc_client
+                    .request_with_policy(request, timeout_millis, retry_policy)
+                    .await
+            }
+            result => result,
+        }
+    }
+
+    /// query config from server, fails if the config does not exist.
+    pub(crate) async fn get_server_config(
+        &self,
+        data_id: &str,
+        group: &str,
+        tenant: &str,
+        read_timeout: u64,
+        notify: bool,
+    ) -> NacosResult<ConfigQueryResponse> {
+        let group = blank2_default_group(group.to_string());
+        let response = self
+            .query_config(data_id, group.as_str(), tenant, read_timeout, notify)
+            .await?;
+        if response.is_success() {
+            return Ok(response);
+        }
+        match response.error_code {
+            ConfigQueryResponse::CONFIG_NOT_FOUND => Err(NacosError::NotFound {
+                code: ConfigQueryResponse::CONFIG_NOT_FOUND as i32,
+                message: format!(
+                    "config not found, dataId={}, group={}, tenant={}",
+                    data_id, group, tenant
+                ),
+            }),
+            ConfigQueryResponse::CONFIG_QUERY_CONFLICT => Err(NacosError::server(
+                codes::CONFLICT,
+                format!(
+                    "config is being modified concurrently, dataId={}, group={}, tenant={}",
+                    data_id, group, tenant
+                ),
+            )),
+            code => Err(NacosError::server(
+                code as i32,
+                format!(
+                    "query config error, dataId={}, group={}, tenant={}, msg={}",
+                    data_id,
+                    group,
+                    tenant,
+                    response.message.as_deref().unwrap_or("")
+                ),
+            )),
+        }
+    }
+
+    /// send [ConfigQueryRequest].
+    async fn query_config(
+        &self,
+        data_id: &str,
+        group: &str,
+        tenant: &str,
+        read_timeout: u64,
+        notify: bool,
+    ) -> NacosResult<ConfigQueryResponse> {
+        let mut request = ConfigQueryRequest::new(
+            data_id.to_string(),
+            group.to_string(),
+            Some(tenant.to_string()),
+        );
+        request
+            .headers
+            .insert(Self::NOTIFY_HEADER.to_string(), notify.to_string());
+        self.request::<_, ConfigQueryResponse>(&request, read_timeout)
+            .await
+    }
+
+    /// publish config to server, a non-empty `cas_md5` makes it a cas publish.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn publish_config(
+        &self,
+        data_id: &str,
+        group: &str,
+        tenant: &str,
+        app_name: Option<String>,
+        tag: Option<String>,
+        beta_ips: Option<String>,
+        content: &str,
+        encrypted_data_key: Option<String>,
+        cas_md5: Option<String>,
+        ty: &str,
+    ) -> NacosResult<bool> {
+        let group = blank2_default_group(group.to_string());
+        let mut request = ConfigPublishRequest::new(
+            data_id.to_string(),
+            group.clone(),
+            Some(tenant.to_string()),
+            content.to_string(),
+        );
+        let cas_md5 = cas_md5.filter(|md5| !md5.trim().is_empty());
+        request.cas_md5 = cas_md5.clone();
+        let params = [
+            (Self::TAG_PARAM, tag),
+            (Self::APP_NAME_PARAM, app_name),
+            (Self::BETA_IPS_PARAM, beta_ips),
+            (Self::TYPE_PARAM, Some(ty.to_string())),
+            (Self::ENCRYPTED_DATA_KEY_PARAM, encrypted_data_key),
+        ];
+        for (key, value) in params {
+            if let Some(value) = value {
+                request.put_addition_param(key, value);
+            }
+        }
+        let retry_policy = publish_retry_policy(&self.retry_policy, cas_md5.is_some());
+        let response = self
+            .request_with_policy::<_, ConfigPublishResponse>(
+                &request,
+                timeout_mills(),
+                &retry_policy,
+            )
+            .await?;
+        if response.is_success() {
+            info!(
+                "publish config ok, dataId={}, group={}, tenant={}",
+                data_id, group, tenant
+            );
+            return Ok(true);
+        }
+        let message = response.message.clone().unwrap_or_default();
+        if cas_md5.is_some() && is_cas_mismatch(message.as_str()) {
+            return Err(NacosError::CasMismatch(CasMismatchError {
+                data_id: data_id.to_string(),
+                group,
+                message,
+            }));
+        }
+        warn!(
+            "publish config fail, dataId={}, group={}, tenant={}, code={}, msg={}",
+            data_id, group, tenant, response.error_code, message
+        );
+        Ok(false)
+    }
+
+    /// remove config from server, the local cache is cleaned up on success unless only
+    /// the `tag` variant is removed.
+    pub(crate) async fn remove_config(
+        &self,
+        data_id: &str,
+        group: &str,
+        tenant: &str,
+        tag: Option<String>,
+    ) -> NacosResult<bool> {
+        let group = blank2_default_group(group.to_string());
+        let tag = tag.filter(|tag| !tag.trim().is_empty());
+        let is_tagged = tag.is_some();
+        let request = ConfigRemoveRequest::new(
+            data_id.to_string(),
+            group.clone(),
+            Some(tenant.to_string()),
+            tag,
+        );
+        let response = self
+            .request::<_, ConfigRemoveResponse>(&request, timeout_mills())
+            .await?;
+        if !response.is_success() {
+            warn!(
+                "remove config fail, dataId={}, group={}, tenant={}, code={}, msg={}",
+                data_id,
+                group,
+                tenant,
+                response.error_code,
+                response.message.as_deref().unwrap_or("")
+            );
+            return Ok(false);
+        }
+        info!(
+            "remove config ok, dataId={}, group={}, tenant={}",
+            data_id, group, tenant
+        );
+        if is_tagged {
+            return Ok(true);
+        }
+        let cache = match get_cache(data_id, group.as_str(), tenant) {
+            Some(cache) => cache,
+            None => return Ok(true),
+        };
+        if cache.listener_count() > 0 {
+            // listened caches are kept, so their listeners get the config if published again.
+            cache.reset();
+            cache.set_sync_with_server(false);
+            self.notify_listen_config();
+        } else if let Err(error) = self.unlisten_task(vec![cache]).await {
+            // the listen task unlistens it later.
+            warn!("unlisten removed config {} failed, {}", data_id, error);
+        }
+        Ok(true)
+    }
+
+    /// spawn the background task which batch-listens configs in [CACHE_MAP].
+    pub(crate) fn start_config_listen(self: &Arc<Self>) {
+        let receiver = self.listen_bell_receiver.lock().unwrap().take();
+        let mut receiver = match receiver {
+            Some(receiver) => receiver,
+            None => return,
+        };
+        let worker = self.clone();
+        tokio::spawn(async move {
+            let mut last_all_sync = Instant::now();
+            loop {
+                let _ = tokio::time::timeout(LISTEN_INTERVAL, receiver.recv()).await;
+                if worker.is_shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
+                let need_all_sync = last_all_sync.elapsed() >= ALL_SYNC_INTERVAL;
+                if need_all_sync {
+                    last_all_sync = Instant::now();
+                }
+                if let Err(error) = worker.execute_config_listen(need_all_sync).await {
+                    warn!("execute config listen failed, {}", error);
+                }
+            }
+            info!("config listen task stopped.");
+        });
+    }
+
+    /// trigger a listen round immediately.
+    pub(crate) fn notify_listen_config(&self) {
+        let _ = self.listen_bell.try_send(());
+    }
+
+    /// listen configs with listeners and unlisten those without, grouped by task id.
+    async fn execute_config_listen(&self, need_all_sync: bool) -> NacosResult<()> {
+        let caches: Vec<Arc<CacheData>> = CACHE_MAP.read().unwrap().values().cloned().collect();
+        let mut listen_tasks: HashMap<usize, Vec<Arc<CacheData>>> = HashMap::new();
+        let mut unlisten_tasks: HashMap<usize, Vec<Arc<CacheData>>> = HashMap::new();
+        for cache in caches {
+            if cache.listener_count() == 0 {
+                unlisten_tasks.entry(cache.task_id).or_default().push(cache);
+            } else if need_all_sync || !cache.is_sync_with_server() {
+                listen_tasks.entry(cache.task_id).or_default().push(cache);
+            }
+        }
+        for (task_id, caches) in listen_tasks {
+            if let Err(error) = self.listen_task(caches).await {
+                warn!("listen config of task {} failed, {}", task_id, error);
+            }
+        }
+        for (task_id, caches) in unlisten_tasks {
+            if let Err(error) = self.unlisten_task(caches).await {
+                warn!("unlisten config of task {} failed, {}", task_id, error);
+            }
+        }
+        Ok(())
+    }
+
+    async fn listen_task(&self, caches: Vec<Arc<CacheData>>) -> NacosResult<()> {
+        let contexts = caches
+            .iter()
+            .map(|cache| listen_context(cache.as_ref()))
+            .collect();
+        let request = ConfigBatchListenRequest::new(Default::default(), true, contexts);
+        let response = self
+            .request::<_, ConfigChangeBatchListenResponse>(&request, timeout_mills())
+            .await?;
+        if !response.is_success() {
+            return Err(NacosError::server(
+                response.error_code as i32,
+                format!(
+                    "batch listen fail, msg={}",
+                    response.message.as_deref().unwrap_or("")
+                ),
+            ));
+        }
+        let mut changed_keys = HashSet::new();
+        for changed in response.changed_configs.iter() {
+            let tenant = changed.tenant.as_deref().unwrap_or("");
+            let key = cache_key(changed.data_id.as_str(), changed.group.as_str(), tenant)?;
+            info!("config changed, key={}", key);
+            changed_keys.insert(key);
+        }
+        for cache in caches {
+            let key = cache_key(
+                cache.data_id.as_str(),
+                cache.group.as_str(),
+                cache.tenant.as_str(),
+            )?;
+            if changed_keys.contains(&key) {
+                self.refresh_content_and_check(cache.as_ref(), true).await;
+            } else {
+                cache.set_sync_with_server(true);
+                cache.check_listener_md5();
+            }
+        }
+        Ok(())
+    }
+
+    async fn unlisten_task(&self, caches: Vec<Arc<CacheData>>) -> NacosResult<()> {
+        let contexts = caches
+            .iter()
+            .map(|cache| listen_context(cache.as_ref()))
+            .collect();
+        let request = ConfigBatchListenRequest::new(Default::default(), false, contexts);
+        let response = self
+            .request::<_, ConfigChangeBatchListenResponse>(&request, timeout_mills())
+            .await?;
+        if !response.is_success() {
+            return Err(NacosError::server(
+                response.error_code as i32,
+                format!(
+                    "batch unlisten fail, msg={}",
+                    response.message.as_deref().unwrap_or("")
+                ),
+            ));
+        }
+        for cache in caches {
+            // a listener may be added while unlistening, the cache is kept and listened again then.
+            let kept = remove_cache_unless_listened(
+                cache.data_id.as_str(),
+                cache.group.as_str(),
+                cache.tenant.as_str(),
+            );
+            if let Some(cache) = kept {
+                cache.set_sync_with_server(false);
+                self.notify_listen_config();
+            }
+        }
+        Ok(())
+    }
+
+    /// query the latest content of cache and notify listeners if it changed.
+    pub(crate) async fn refresh_content_and_check(&self, cache: &CacheData, notify: bool) {
+        let response = self
+            .query_config(
+                cache.data_id.as_str(),
+                cache.group.as_str(),
+                cache.tenant.as_str(),
+                timeout_mills(),
+                notify,
+            )
+            .await;
+        match response {
+            Ok(response)
+                if response.is_success()
+                    || response.error_code == ConfigQueryResponse::CONFIG_NOT_FOUND =>
+            {
+                cache.update(&response);
+                cache.set_sync_with_server(true);
+                cache.check_listener_md5();
+            }
+            Ok(response) => {
+                warn!(
+                    "refresh config fail, dataId={}, group={}, code={}, msg={}",
+                    cache.data_id,
+                    cache.group,
+                    response.error_code,
+                    response.message.as_deref().unwrap_or("")
+                );
+            }
+            Err(error) => {
+                warn!(
+                    "refresh config error, dataId={}, group={}, {}",
+                    cache.data_id, cache.group, error
+                );
+            }
+        }
+    }
+
+    /// whether the client is connected to a server.
+    pub(crate) fn is_health_server(&self) -> bool {
+        self.rpc_client
+            .as_ref()
+            .map(|rpc_client| rpc_client.state() == ClientState::Running)
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn shutdown(&self) {
+        self.is_shutdown.store(true, Ordering::SeqCst);
+        self.notify_listen_config();
+        if let Some(rpc_client) = self.rpc_client.as_ref() {
+            rpc_client.shutdown();
+        }
+    }
+
+    /// register a listener of config, the listener is removed when the returned handle is dropped.
+    /// The cache is initialized with `response` if present, so the listener is only notified of later changes.
+    pub(crate) fn add_tenant_listener<L>(
+        &self,
+        data_id: &str,
+        group: &str,
+        tenant: &str,
+        response: Option<&ConfigQueryResponse>,
+        listener: L,
+    ) -> NacosResult<ListenerHandle>
+    where
+        L: Fn(ConfigInfo) + Send + Sync + 'static,
+    {
+        let group = blank2_default_group(group.to_string());
+        // subscribe in the lock of CACHE_MAP, so an unlistening task never removes the cache
+        // between adding it and subscribing.
+        let mut lock = CACHE_MAP.write().unwrap();
+        let cache = add_cache_data_if_absent(&mut lock, data_id, group.as_str(), tenant)?;
+        if let Some(response) = response {
+            cache.update(response);
+        }
+        let subscription = cache.add_listener(listener);
+        drop(lock);
+        if !cache.is_sync_with_server() {
+            self.notify_listen_config();
+        }
+        Ok(ListenerHandle::new(cache.listeners(), subscription))
+    }
+}
+
+/// whether the failed response to a cas publish is caused by md5 mismatch.
+fn is_cas_mismatch(message: &str) -> bool {
+    message
+        .to_lowercase()
+        .contains(ClientWorker::CAS_MISMATCH_MESSAGE)
+}
+
+/// a cas publish is never retried, as the retry of a publish applied but timed out is
+/// rejected for the md5 changed by itself.
+fn publish_retry_policy(retry_policy: &RetryPolicy, is_cas: bool) -> RetryPolicy {
+    if is_cas {
+        RetryPolicy::no_retry()
+    } else {
+        retry_policy.clone()
     }
 }
 
@@ -91,38 +671,132 @@ fn blank2_default_group(group: String) -> String {
         group.trim().to_string()
     }
 }
-fn get_cache(data_id: &str, group: &str) -> Option<Arc<CacheData>> {
-    let tenant = tenant::get_user_tenant_for_acm();
-    let group_key = GroupKey::new(data_id, group, tenant.as_str()).unwrap();
-    let group_key = group_key.to_string();
-    let cache = {
-        let lock = CACHE_MAP.read().unwrap();
-        let cache = lock.get(group_key.as_str());
+
+/// build [RetryPolicy] from `max_retry` and `retry_time` (backoff in milliseconds) properties.
+pub(crate) fn retry_policy(max_retry: Option<&str>, retry_time: Option<&str>) -> RetryPolicy {
+    let max_retry = max_retry
+        .and_then(|max_retry| max_retry.trim().parse::<u32>().ok())
+        .unwrap_or(val::MAX_RETRY as u32);
+    let retry_time = retry_time
+        .and_then(|retry_time| retry_time.trim().parse::<u64>().ok())
+        .unwrap_or(val::CONFIG_RETRY_TIME as u64);
+    RetryPolicy::new(max_retry + 1, Duration::from_millis(retry_time))
+}
+/// Handler of [ConfigChangeNotifyRequest], which re-listens the changed config immediately.
+struct ConfigChangeNotifyHandler {
+    listen_bell: mpsc::Sender<()>,
+}
+
+#[tonic::async_trait]
+impl ServerRequestHandler for ConfigChangeNotifyHandler {
+    type Request = ConfigChangeNotifyRequest;
+    type Response = ConfigChangeNotifyResponse;
+
+    async fn request_reply(&self, request: Self::Request) -> NacosResult<Option<Self::Response>> {
+        let tenant = request.tenant.as_deref().unwrap_or("");
+        let cache = get_cache(request.data_id.as_str(), request.group.as_str(), tenant);
         if let Some(cache) = cache {
-            Some(cache.clone())
-        } else {
-            None
+            info!(
+                "config change notified, dataId={}, group={}, tenant={}",
+                request.data_id, request.group, tenant
+            );
+            cache.set_sync_with_server(false);
+            let _ = self.listen_bell.try_send(());
+        }
+        Ok(Some(ConfigChangeNotifyResponse::new(None)))
+    }
+}
+
+/// Re-listens all configs after reconnecting, since the new server knows nothing about them.
+struct ConfigConnectionEventListener {
+    listen_bell: mpsc::Sender<()>,
+}
+
+impl ConnectionEventListener for ConfigConnectionEventListener {
+    fn on_connected(&self) {
+        info!("connected to server, re-listen all configs.");
+        let _ = self.listen_bell.try_send(());
+    }
+
+    fn on_disconnect(&self) {
+        let cache_map = CACHE_MAP.read().unwrap();
+        for cache in cache_map.values() {
+            cache.set_sync_with_server(false);
+        }
+    }
+}
+
+/// Switches server once the server in use is removed from server list.
+struct ServerListWatcher(Weak<RpcClient>);
+
+impl ServerListChangedListener for ServerListWatcher {
+    fn on_changed(&self, event: &ServerListChangedEvent) {
+        if let Some(rpc_client) = self.0.upgrade() {
+            rpc_client.on_server_list_changed(&event.servers);
         }
-    };
-    cache
+    }
+}
+
+fn listen_context(cache: &CacheData) -> ConfigListenContext {
+    ConfigListenContext::new(
+        cache.group.clone(),
+        Some(cache.md5()),
+        cache.data_id.clone(),
+        Some(cache.tenant.clone()),
+    )
 }
-fn add_cache_data_if_absent(data_id: String, group: String) -> Option<Arc<CacheData>> {
-    let cache = get_cache(data_id.as_str(), group.as_str());
-    return if let Some(cache) = cache {
-        Some(cache)
+/// [GroupKey] of config, tenant is ignored if blank.
+fn group_key(data_id: &str, group: &str, tenant: &str) -> NacosResult<GroupKey> {
+    if tenant.trim().is_empty() {
+        GroupKey::new_without_tenant(data_id, group)
     } else {
-        let key = GroupKey::new_without_tenant(data_id.as_str(), group.as_str()).unwrap();
-        let cache = CacheData {};
-        let task_id = { CACHE_MAP.read().unwrap().len() / 3000 };
-        {
-            let mut write = CACHE_MAP.write().unwrap();
-            let cache = get_cache(data_id.as_str(), group.as_str());
+        GroupKey::new(data_id, group, tenant)
+    }
+}
+/// key of [CACHE_MAP].
+fn cache_key(data_id: &str, group: &str, tenant: &str) -> NacosResult<String> {
+    Ok(group_key(data_id, group, tenant)?.to_string())
+}
+fn get_cache(data_id: &str, group: &str, tenant: &str) -> Option<Arc<CacheData>> {
+    let group_key = cache_key(data_id, group, tenant).ok()?;
+    let lock = CACHE_MAP.read().unwrap();
+    lock.get(group_key.as_str()).cloned()
+}
+/// remove the cache of config if it has no listener, or else return the cache kept.
+fn remove_cache_unless_listened(
+    data_id: &str,
+    group: &str,
+    tenant: &str,
+) -> Option<Arc<CacheData>> {
+    let group_key = cache_key(data_id, group, tenant).ok()?;
+    let mut lock = CACHE_MAP.write().unwrap();
+    match lock.get(group_key.as_str()) {
+        Some(cache) if cache.listener_count() > 0 => Some(cache.clone()),
+        _ => {
+            lock.remove(group_key.as_str());
+            None
         }
-        None
-    };
+    }
+}
+/// add a [CacheData] if absent, caches are sharded into listen tasks by insertion order.
+fn add_cache_data_if_absent(
+    lock: &mut HashMap<String, Arc<CacheData>>,
+    data_id: &str,
+    group: &str,
+    tenant: &str,
+) -> NacosResult<Arc<CacheData>> {
+    let group_key = group_key(data_id, group, tenant)?;
+    let key = group_key.to_string();
+    if let Some(cache) = lock.get(key.as_str()) {
+        return Ok(cache.clone());
+    }
+    let task_id = lock.len() / PER_TASK_CONFIG_SIZE;
+    let cache = Arc::new(CacheData::new(group_key, data_id, group, tenant, task_id));
+    lock.insert(key, cache.clone());
+    Ok(cache)
 }
 
-mod tenant {
+pub(crate) mod tenant {
     use lazy_static::lazy_static;
     const ACM_NAMESPACE_PROPERTY: &'static str = "acm.namespace";
     const DEFAULT_ACM_NAMESPACE: &'static str = "";
@@ -130,7 +804,7 @@ mod tenant {
         static ref USER_TENANT: String = { std::env::var("tenant.id").unwrap_or("".to_string()) };
     }
 
-    pub fn get_user_tenant_for_acm() -> String {
+    pub(crate) fn get_user_tenant_for_acm() -> String {
         let mut tmp = USER_TENANT.clone();
 
         if tmp.trim().is_empty() {
@@ -145,10 +819,12 @@ mod tenant {
 #[test]
 fn test_read_cache() {
     {
+        let group_key = GroupKey::new_without_tenant("hello", "pay").unwrap();
+        let cache = CacheData::new(group_key, "hello", "pay", "", 0);
         CACHE_MAP
             .write()
             .unwrap()
-            .insert("hello".to_string(), Arc::new(CacheData));
+            .insert("hello".to_string(), Arc::new(cache));
     }
     let read = CACHE_MAP.read().unwrap();
     let data = read.get("hello");
@@ -158,3 +834,31 @@ fn test_read_cache() {
         assert!(false);
     }
 }
+
+#[test]
+fn test_retry_policy_from_properties() {
+    let policy = retry_policy(Some("5"), Some("200"));
+    assert_eq!(policy.max_attempts, 6);
+    assert_eq!(policy.initial_backoff, Duration::from_millis(200));
+    let policy = retry_policy(None, Some("invalid"));
+    assert_eq!(policy.max_attempts, val::MAX_RETRY as u32 + 1);
+    assert_eq!(
+        policy.initial_backoff,
+        Duration::from_millis(val::CONFIG_RETRY_TIME as u64)
+    );
+}
+
+#[test]
+fn test_is_cas_mismatch() {
+    assert!(is_cas_mismatch(
+        "Cas publish fail, server md5 may have changed."
+    ));
+    assert!(!is_cas_mismatch("publish config error"));
+}
+
+#[test]
+fn test_publish_retry_policy() {
+    let policy = retry_policy(Some("3"), None);
+    assert_eq!(publish_retry_policy(&policy, false).max_attempts, 4);
+    assert_eq!(publish_retry_policy(&policy, true).max_attempts, 1);
+}
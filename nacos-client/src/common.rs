@@ -11,9 +11,9 @@ pub struct GroupKey {
     tenant: Option<String>,
 }
 
-fn require_nonnull(name: &str, value: &str) -> NacosResult<()> {
+pub(crate) fn require_nonnull(name: &str, value: &str) -> NacosResult<()> {
     if value.trim().is_empty() {
-        return Err(NacosError::msg(format!(
+        return Err(NacosError::client(format!(
             "invalid value {}, please check",
             name
         )));
@@ -64,12 +64,12 @@ impl TryFrom<String> for GroupKey {
         let data_id = if let Some(data) = split.next() {
             data
         } else {
-            return Err(NacosError::msg("data id parse error"));
+            return Err(NacosError::client("data id parse error"));
         };
         let group_id = if let Some(data) = split.next() {
             data
         } else {
-            return Err(NacosError::msg("group parse error"));
+            return Err(NacosError::client("group parse error"));
         };
 
         let tenant = if let Some(t) = split.next() {
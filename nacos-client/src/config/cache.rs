@@ -1 +1,251 @@
-pub struct CacheData {}
+use crate::common::GroupKey;
+use crate::crypto::get_md5_string;
+use crate::listeners::{ConfigInfo, ListenerSet, Subscription};
+use nacos_api::api::remote::response::ConfigQueryResponse;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, RwLock};
+
+#[derive(Debug, Clone, Default)]
+struct CacheContent {
+    content: String,
+    md5: String,
+    ty: String,
+    encrypted_data_key: Option<String>,
+    last_modified: i64,
+}
+
+/// Local cache of a listened config.
+pub struct CacheData {
+    pub data_id: String,
+    pub group: String,
+    pub tenant: String,
+    pub task_id: usize,
+    group_key: GroupKey,
+    inner: RwLock<CacheContent>,
+    listeners: ListenerSet<ConfigInfo>,
+    // md5 of content last delivered to each listener.
+    last_call_md5: Mutex<HashMap<Subscription, String>>,
+    is_sync_with_server: AtomicBool,
+}
+
+impl CacheData {
+    pub fn new(
+        group_key: GroupKey,
+        data_id: &str,
+        group: &str,
+        tenant: &str,
+        task_id: usize,
+    ) -> Self {
+        CacheData {
+            data_id: data_id.to_string(),
+            group: group.to_string(),
+            tenant: tenant.to_string(),
+            task_id,
+            group_key,
+            inner: RwLock::new(CacheContent::default()),
+            listeners: ListenerSet::new(),
+            last_call_md5: Mutex::new(HashMap::new()),
+            is_sync_with_server: AtomicBool::new(false),
+        }
+    }
+
+    /// md5 of content, empty if the content is empty.
+    pub fn get_md5_string(content: &str) -> String {
+        if content.is_empty() {
+            "".to_string()
+        } else {
+            get_md5_string(content)
+        }
+    }
+
+    pub fn md5(&self) -> String {
+        self.inner.read().unwrap().md5.clone()
+    }
+
+    /// update content and recalculate md5.
+    #[cfg(test)]
+    pub fn set_content(&self, content: &str) {
+        let mut lock = self.inner.write().unwrap();
+        lock.content = content.to_string();
+        lock.md5 = Self::get_md5_string(content);
+    }
+
+    /// clear the content, e.g. after the config is removed from server.
+    pub fn reset(&self) {
+        *self.inner.write().unwrap() = CacheContent::default();
+    }
+
+    /// update cache from the server query response.
+    pub fn update(&self, response: &ConfigQueryResponse) {
+        let content = response.content.clone().unwrap_or_default();
+        let mut lock = self.inner.write().unwrap();
+        lock.md5 = Self::get_md5_string(content.as_str());
+        lock.content = content;
+        lock.ty = response.content_type.clone().unwrap_or_default();
+        lock.encrypted_data_key = response.encrypted_data_key.clone();
+        lock.last_modified = response.last_modified;
+    }
+
+    /// whether the cache is listened by server with the current md5.
+    pub fn is_sync_with_server(&self) -> bool {
+        self.is_sync_with_server.load(Ordering::SeqCst)
+    }
+
+    pub fn set_sync_with_server(&self, sync: bool) {
+        self.is_sync_with_server.store(sync, Ordering::SeqCst);
+    }
+
+    /// listeners of this cache.
+    pub fn listeners(&self) -> ListenerSet<ConfigInfo> {
+        self.listeners.clone()
+    }
+
+    /// add a listener, which is notified only when content changes from now on.
+    pub fn add_listener<L>(&self, listener: L) -> Subscription
+    where
+        L: Fn(ConfigInfo) + Send + Sync + 'static,
+    {
+        let md5 = self.md5();
+        let mut marks = self.last_call_md5.lock().unwrap();
+        let subscription = self.listeners.subscribe(self.group_key.clone(), listener);
+        marks.insert(subscription.clone(), md5);
+        subscription
+    }
+
+    pub fn listener_count(&self) -> usize {
+        self.listeners.len()
+    }
+
+    fn config_info(&self) -> ConfigInfo {
+        let inner = self.inner.read().unwrap();
+        ConfigInfo {
+            data_id: self.data_id.clone(),
+            group: self.group.clone(),
+            tenant: self.tenant.clone(),
+            content: inner.content.clone(),
+            md5: inner.md5.clone(),
+            ty: inner.ty.clone(),
+            encrypted_data_key: inner.encrypted_data_key.clone(),
+            last_modified: inner.last_modified,
+        }
+    }
+
+    /// notify every listener whose last notified md5 differs from the current one.
+    pub fn check_listener_md5(&self) {
+        let info = self.config_info();
+        let listeners = self.listeners.entries();
+        let to_notify: Vec<_> = {
+            let mut marks = self.last_call_md5.lock().unwrap();
+            marks.retain(|subscription, _| listeners.iter().any(|(s, _)| s == subscription));
+            listeners
+                .into_iter()
+                .filter(|(subscription, _)| {
+                    let last = marks.entry(subscription.clone()).or_default();
+                    if *last == info.md5 {
+                        false
+                    } else {
+                        *last = info.md5.clone();
+                        true
+                    }
+                })
+                .map(|(_, listener)| listener)
+                .collect()
+        };
+        for listener in to_notify {
+            debug!(
+                "notify listener, dataId={}, group={}, md5={}",
+                info.data_id, info.group, info.md5
+            );
+            listener(info.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CacheData;
+    use crate::common::GroupKey;
+    use nacos_api::api::remote::response::ConfigQueryResponse;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    fn cache() -> CacheData {
+        let group_key = GroupKey::new_without_tenant("app", "DEFAULT_GROUP").unwrap();
+        CacheData::new(group_key, "app", "DEFAULT_GROUP", "", 0)
+    }
+
+    #[test]
+    fn test_notify_once_per_change() {
+        let cache = cache();
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = count.clone();
+        cache.add_listener(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        cache.check_listener_md5();
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+
+        cache.set_content("k=v");
+        cache.check_listener_md5();
+        cache.check_listener_md5();
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        cache.set_content("k=v");
+        cache.check_listener_md5();
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        cache.set_content("k=v2");
+        cache.check_listener_md5();
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_reset_notifies_removal() {
+        let cache = cache();
+        cache.set_content("k=v");
+        let contents = Arc::new(Mutex::new(vec![]));
+        let received = contents.clone();
+        cache.add_listener(move |info| received.lock().unwrap().push(info.content));
+        cache.reset();
+        assert_eq!(cache.md5(), "");
+        cache.check_listener_md5();
+        // published again after removal.
+        cache.set_content("k=v");
+        cache.check_listener_md5();
+        assert_eq!(*contents.lock().unwrap(), vec!["", "k=v"]);
+    }
+
+    #[test]
+    fn test_deliver_query_response() {
+        let cache = cache();
+        let infos = Arc::new(Mutex::new(vec![]));
+        let received = infos.clone();
+        cache.add_listener(move |info| received.lock().unwrap().push(info));
+        let body = r#"{"resultCode":200,"errorCode":0,"content":"k=v","md5":"md5","contentType":"properties","encryptedDataKey":"key","lastModified":1000}"#;
+        let response: ConfigQueryResponse = serde_json::from_str(body).unwrap();
+        cache.update(&response);
+        cache.check_listener_md5();
+        let infos = infos.lock().unwrap();
+        assert_eq!(infos[0].ty, "properties");
+        assert_eq!(infos[0].encrypted_data_key.as_deref(), Some("key"));
+        assert_eq!(infos[0].last_modified, 1000);
+    }
+
+    #[test]
+    fn test_new_listener_skips_current_content() {
+        let cache = cache();
+        cache.set_content("k=v");
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = count.clone();
+        cache.add_listener(move |info| {
+            assert_eq!(info.content, "k=v2");
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        cache.check_listener_md5();
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+        cache.set_content("k=v2");
+        cache.check_listener_md5();
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+}
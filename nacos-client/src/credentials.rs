@@ -0,0 +1,318 @@
+//! A module to provide credentials for login and request signing, which may rotate at runtime.
+use crate::security::Credentials;
+use crate::Properties;
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use nacos_api::api::consts::{names, val};
+use nacos_core::error::{NacosError, NacosResult};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+
+pub const ENV_USERNAME: &'static str = "NACOS_USERNAME";
+pub const ENV_PASSWORD: &'static str = "NACOS_PASSWORD";
+pub const ENV_ACCESS_KEY: &'static str = "NACOS_ACCESS_KEY";
+pub const ENV_SECRET_KEY: &'static str = "NACOS_SECRET_KEY";
+/// refresh sts credentials this long before they expire.
+const STS_REFRESH_AHEAD: Duration = Duration::from_secs(3 * 60);
+/// interval to retry fetching sts credentials after a failure.
+const STS_RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Provides the latest [Credentials], called for every login and signed request.
+#[tonic::async_trait]
+pub trait CredentialsProvider: Debug + Send + Sync {
+    fn credentials(&self) -> Credentials;
+
+    /// fetch credentials from remote, called before the client starts and then
+    /// every [CredentialsProvider::refresh_interval].
+    async fn refresh(&self) -> NacosResult<()> {
+        Ok(())
+    }
+
+    /// interval to the next [CredentialsProvider::refresh], `None` if never refreshed.
+    fn refresh_interval(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Credentials fixed since the client starts.
+#[derive(Debug, Clone)]
+pub struct StaticCredentialsProvider(pub Credentials);
+
+impl CredentialsProvider for StaticCredentialsProvider {
+    fn credentials(&self) -> Credentials {
+        self.0.clone()
+    }
+}
+
+/// Credentials read from environment variables `NACOS_USERNAME`, `NACOS_PASSWORD`,
+/// `NACOS_ACCESS_KEY` and `NACOS_SECRET_KEY` on every call.
+#[derive(Debug, Clone, Default)]
+pub struct EnvCredentialsProvider;
+
+impl CredentialsProvider for EnvCredentialsProvider {
+    fn credentials(&self) -> Credentials {
+        let var = |name| std::env::var(name).ok();
+        Credentials::new(var(ENV_USERNAME), var(ENV_PASSWORD))
+            .with_access_key(var(ENV_ACCESS_KEY), var(ENV_SECRET_KEY))
+    }
+}
+
+/// Credentials read from a file of `key=value` lines, e.g. a mounted secret, keys are
+/// `username`, `password`, `access_key` and `secret_key`. The file is reloaded once it
+/// is modified, and the last credentials are kept if it fails to reload.
+#[derive(Debug)]
+pub struct FileCredentialsProvider {
+    path: PathBuf,
+    // credentials loaded and modified time of the file then.
+    loaded: Mutex<(Credentials, Option<SystemTime>)>,
+}
+
+impl FileCredentialsProvider {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        let provider = FileCredentialsProvider {
+            path: path.into(),
+            loaded: Mutex::new((Credentials::default(), None)),
+        };
+        provider.reload_if_modified();
+        provider
+    }
+
+    fn reload_if_modified(&self) -> Credentials {
+        let mut loaded = self.loaded.lock().unwrap();
+        let modified = std::fs::metadata(&self.path).and_then(|metadata| metadata.modified());
+        match modified {
+            Ok(modified) if loaded.1 != Some(modified) => match self.load() {
+                Ok(credentials) => {
+                    info!("credentials reloaded from {}", self.path.display());
+                    *loaded = (credentials, Some(modified));
+                }
+                Err(error) => warn!(
+                    "load credentials from {} failed, {}",
+                    self.path.display(),
+                    error
+                ),
+            },
+            Ok(_) => {}
+            Err(error) => warn!(
+                "credentials file {} is not readable, {}",
+                self.path.display(),
+                error
+            ),
+        }
+        loaded.0.clone()
+    }
+
+    fn load(&self) -> NacosResult<Credentials> {
+        let content = std::fs::read_to_string(&self.path)?;
+        let mut values: HashMap<&str, String> = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim(), value.trim().to_string()))
+            .collect();
+        Ok(Credentials::new(
+            values.remove(names::USERNAME),
+            values.remove(names::PASSWORD),
+        )
+        .with_access_key(
+            values.remove(names::ACCESS_KEY),
+            values.remove(names::SECRET_KEY),
+        ))
+    }
+}
+
+impl CredentialsProvider for FileCredentialsProvider {
+    fn credentials(&self) -> Credentials {
+        self.reload_if_modified()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct StsResponse {
+    access_key_id: String,
+    access_key_secret: String,
+    security_token: Option<String>,
+    expiration: String,
+}
+
+/// Temporary access key and secret key of a ram role, fetched from the local metadata
+/// endpoint `<metadata_url><ram_role_name>` and refreshed before they expire.
+#[derive(Debug)]
+pub struct StsCredentialsProvider {
+    url: String,
+    // credentials fetched and when they expire.
+    fetched: RwLock<Option<(Credentials, DateTime<Utc>)>>,
+}
+
+impl StsCredentialsProvider {
+    /// `metadata_url` is like `http://100.100.100.200/latest/meta-data/ram/security-credentials/`.
+    pub fn new(metadata_url: &str, ram_role_name: &str) -> Self {
+        StsCredentialsProvider {
+            url: format!("{}/{}", metadata_url.trim_end_matches('/'), ram_role_name),
+            fetched: RwLock::new(None),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl CredentialsProvider for StsCredentialsProvider {
+    fn credentials(&self) -> Credentials {
+        match self.fetched.read().unwrap().as_ref() {
+            Some((credentials, _)) => credentials.clone(),
+            None => Credentials::default(),
+        }
+    }
+
+    async fn refresh(&self) -> NacosResult<()> {
+        let body = crate::http::get(self.url.clone()).await?;
+        let response = serde_json::from_str::<StsResponse>(body.as_str())?;
+        let expiration = DateTime::parse_from_rfc3339(response.expiration.as_str())
+            .map_err(|error| {
+                NacosError::Serialization(format!(
+                    "invalid expiration `{}` of sts credentials, {}",
+                    response.expiration, error
+                ))
+            })?
+            .with_timezone(&Utc);
+        let mut credentials = Credentials::default().with_access_key(
+            Some(response.access_key_id),
+            Some(response.access_key_secret),
+        );
+        credentials.security_token = response.security_token;
+        info!("sts credentials refreshed, expire at {}", expiration);
+        *self.fetched.write().unwrap() = Some((credentials, expiration));
+        Ok(())
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        let interval = match self.fetched.read().unwrap().as_ref() {
+            Some((_, expiration)) => (*expiration - Utc::now())
+                .to_std()
+                .unwrap_or(Duration::ZERO)
+                .saturating_sub(STS_REFRESH_AHEAD),
+            None => Duration::ZERO,
+        };
+        Some(interval.max(STS_RETRY_INTERVAL))
+    }
+}
+
+/// create the provider specified in `properties`, in the order of sts provider if `ram_role_name`
+/// is specified, file provider if `credentials_path` is specified, static provider if any of
+/// `username` and `access_key` is specified, or else the environment variables provider.
+pub fn from_properties(properties: &Properties) -> Arc<dyn CredentialsProvider> {
+    let property = |name| {
+        properties
+            .get(name)
+            .map(|value: &String| value.trim())
+            .filter(|value| !value.is_empty())
+    };
+    if let Some(ram_role_name) = property(names::RAM_ROLE_NAME) {
+        let metadata_url = property(names::RAM_METADATA_URL).unwrap_or(val::RAM_METADATA_URL);
+        return Arc::new(StsCredentialsProvider::new(metadata_url, ram_role_name));
+    }
+    if let Some(path) = property(names::CREDENTIALS_PATH) {
+        return Arc::new(FileCredentialsProvider::new(path));
+    }
+    if property(names::USERNAME).is_some() || property(names::ACCESS_KEY).is_some() {
+        let value = |name| properties.get(name).cloned();
+        let credentials = Credentials::new(value(names::USERNAME), value(names::PASSWORD))
+            .with_access_key(value(names::ACCESS_KEY), value(names::SECRET_KEY));
+        return Arc::new(StaticCredentialsProvider(credentials));
+    }
+    Arc::new(EnvCredentialsProvider)
+}
+
+/// refresh `provider` in background by its [CredentialsProvider::refresh_interval],
+/// until the provider is dropped.
+pub(crate) fn start_refresh(provider: &Arc<dyn CredentialsProvider>) {
+    if provider.refresh_interval().is_none() {
+        return;
+    }
+    let provider = Arc::downgrade(provider);
+    tokio::spawn(async move {
+        // the provider is not kept alive while sleeping.
+        while let Some(interval) = provider.upgrade().and_then(|p| p.refresh_interval()) {
+            tokio::time::sleep(interval).await;
+            if let Some(provider) = provider.upgrade() {
+                if let Err(error) = provider.refresh().await {
+                    warn!("refresh credentials failed, {}", error);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        from_properties, CredentialsProvider, EnvCredentialsProvider, FileCredentialsProvider,
+        StsCredentialsProvider, ENV_ACCESS_KEY, ENV_SECRET_KEY,
+    };
+    use nacos_api::api::consts::names;
+    use std::collections::HashMap;
+    use std::io::Write;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_from_properties() {
+        let mut properties = HashMap::new();
+        properties.insert(names::USERNAME.to_string(), "nacos".to_string());
+        properties.insert(names::PASSWORD.to_string(), "nacos".to_string());
+        let credentials = from_properties(&properties).credentials();
+        assert_eq!(credentials.username.as_deref(), Some("nacos"));
+        assert!(credentials.access_key_pair().is_none());
+        properties.insert(names::RAM_ROLE_NAME.to_string(), "role".to_string());
+        assert!(from_properties(&properties).refresh_interval().is_some());
+    }
+
+    #[test]
+    fn test_env_provider() {
+        std::env::set_var(ENV_ACCESS_KEY, "ak");
+        std::env::set_var(ENV_SECRET_KEY, "sk");
+        let credentials = EnvCredentialsProvider.credentials();
+        assert_eq!(credentials.access_key_pair(), Some(("ak", "sk")));
+        std::env::remove_var(ENV_ACCESS_KEY);
+        std::env::remove_var(ENV_SECRET_KEY);
+    }
+
+    #[test]
+    fn test_file_provider_reload() {
+        let path = std::env::temp_dir().join(format!("nacos-credentials-{}", std::process::id()));
+        std::fs::write(
+            &path,
+            "# rotated by operator\nusername=nacos\npassword=old\n",
+        )
+        .unwrap();
+        let provider = FileCredentialsProvider::new(&path);
+        assert_eq!(provider.credentials().password.as_deref(), Some("old"));
+        let mut file = std::fs::File::create(&path).unwrap();
+        file.write_all(b"username=nacos\npassword=new\n").unwrap();
+        // make sure the modified time changes on file systems of coarse timestamps.
+        file.set_modified(SystemTime::now() + Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(provider.credentials().password.as_deref(), Some("new"));
+        // keep the last credentials if the file is gone.
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(provider.credentials().password.as_deref(), Some("new"));
+    }
+
+    #[test]
+    fn test_sts_provider() {
+        let body = r#"{"AccessKeyId":"ak","AccessKeySecret":"sk","SecurityToken":"token","Expiration":"2099-01-01T00:00:00Z","Code":"Success"}"#;
+        let port = crate::http::stub::serve(body);
+        let provider = StsCredentialsProvider::new(&format!("http://127.0.0.1:{}/", port), "role");
+        assert!(provider.credentials().access_key_pair().is_none());
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(provider.refresh()).unwrap();
+        let credentials = provider.credentials();
+        assert_eq!(credentials.access_key_pair(), Some(("ak", "sk")));
+        assert_eq!(credentials.security_token.as_deref(), Some("token"));
+        assert!(provider.refresh_interval().unwrap() > Duration::from_secs(3600));
+    }
+}
@@ -1,9 +1,25 @@
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
 /// get md5 string with lower case.
 pub fn get_md5_string(message: &str) -> String {
     let digest = md5::compute(message.as_bytes());
     format!("{:?}", digest)
 }
 
+/// sign `data` with HMAC-SHA1, the signature is encoded in base64.
+pub fn sign_with_hmac_sha1(data: &str, key: &str) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key.as_bytes()).expect("HMAC accepts any key size");
+    mac.update(data.as_bytes());
+    base64::encode(mac.finalize().into_bytes())
+}
+
+#[test]
+fn test_hmac_sha1() {
+    let signature = sign_with_hmac_sha1("The quick brown fox jumps over the lazy dog", "key");
+    assert_eq!(signature, "3nybhbi3iqa8ino29wqQcBydtNk=");
+}
+
 #[test]
 fn test_md5() {
     let digest = get_md5_string("hello world");
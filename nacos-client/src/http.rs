@@ -2,6 +2,42 @@ use log::{debug, error};
 use nacos_core::error::{NacosError, NacosResult};
 use reqwest::ClientBuilder;
 use std::time::Duration;
+
+/// send a get request to `url` and return the body, fails if the status is not success.
+pub async fn get(url: String) -> NacosResult<String> {
+    let client = ClientBuilder::new()
+        .https_only(false)
+        .timeout(Duration::from_secs(15))
+        .connect_timeout(Duration::from_secs(5))
+        .no_proxy()
+        .gzip(true)
+        .build()
+        .unwrap();
+    debug!("http get url : {}", &url);
+    let response = client.get(&url).send().await;
+    match response {
+        Ok(resp) => {
+            let code = resp.status();
+            if code.is_success() {
+                Ok(resp.text_with_charset("UTF-8").await?)
+            } else {
+                Err(NacosError::server(
+                    code.as_u16() as i32,
+                    format!(
+                        "http get {} error for: {}",
+                        &url,
+                        code.canonical_reason().unwrap_or("unknown error.")
+                    ),
+                ))
+            }
+        }
+        Err(e) => {
+            error!("http response error: {:?}", e);
+            Err(NacosError::from(e))
+        }
+    }
+}
+
 pub async fn post_form<'a>(
     url: String,
     params: &'a [(&'static str, &'a str)],
@@ -23,16 +59,46 @@ pub async fn post_form<'a>(
             if code.is_success() {
                 Ok(resp.text_with_charset("UTF-8").await?)
             } else {
-                Err(NacosError::msg(format!(
-                    "config to {} error for: {}",
-                    &url,
-                    code.canonical_reason().unwrap_or("unknown error.")
-                )))
+                Err(NacosError::server(
+                    code.as_u16() as i32,
+                    format!(
+                        "config to {} error for: {}",
+                        &url,
+                        code.canonical_reason().unwrap_or("unknown error.")
+                    ),
+                ))
             }
         }
         Err(e) => {
             error!("http response error: {:?}", e);
-            return Err(NacosError::new(e));
+            Err(NacosError::from(e))
         }
     }
 }
+
+/// A stub http server for tests.
+#[cfg(test)]
+pub(crate) mod stub {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// start a server answering every request with `body`, returns its port.
+    pub(crate) fn serve(body: &str) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        port
+    }
+}
@@ -8,16 +8,16 @@ extern crate prost_types;
 extern crate state;
 extern crate tonic;
 
-mod cache;
 pub mod client;
 mod common;
 mod config;
 pub mod core;
+mod credentials;
 mod crypto;
-mod grpc;
 mod http;
 mod listeners;
 mod security;
+mod spas;
 mod utils;
 
 use std::collections::HashMap;
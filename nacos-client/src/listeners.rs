@@ -1,18 +1,25 @@
 use crate::common::GroupKey;
-use std::boxed::Box;
 use std::collections::HashMap;
 use std::hash::Hash;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
+static SUBSCRIPTION_ID: AtomicUsize = AtomicUsize::new(0);
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
-pub struct Subscription(GroupKey);
+pub struct Subscription {
+    group_key: GroupKey,
+    id: usize,
+}
 impl Subscription {
     fn new(group_key: GroupKey) -> Self {
-        Subscription(group_key)
+        let id = SUBSCRIPTION_ID.fetch_add(1, Ordering::SeqCst);
+        Subscription { group_key, id }
     }
 }
 
-type ListenerMap<T> = HashMap<Subscription, Box<dyn Fn(T) + Send + 'static>>;
+type SharedListener<T> = Arc<dyn Fn(T) + Send + Sync + 'static>;
+type ListenerMap<T> = HashMap<Subscription, SharedListener<T>>;
 
 /// Safety access to listeners, etc...
 #[derive(Clone)]
@@ -34,14 +41,14 @@ where
     }
 
     /// create a new subscribe.
-    pub fn subscribe<Listener: Fn(T) + Send + 'static>(
+    pub fn subscribe<Listener: Fn(T) + Send + Sync + 'static>(
         &self,
         group_key: GroupKey,
         listener: Listener,
     ) -> Subscription {
         let mut lock = self.listeners.lock().unwrap();
         let subscription = Subscription::new(group_key);
-        lock.insert(subscription.clone(), Box::new(listener));
+        lock.insert(subscription.clone(), Arc::new(listener));
         subscription
     }
 
@@ -51,19 +58,80 @@ where
         lock.remove(&subscription);
     }
 
+    /// notify all listeners, listeners are called without holding the lock.
     pub fn notify(&self, payload: &T) {
-        let listeners = self.listeners.lock().unwrap();
-        for listener in listeners.values() {
+        let listeners: Vec<_> = {
+            let lock = self.listeners.lock().unwrap();
+            lock.values().cloned().collect()
+        };
+        for listener in listeners {
             listener(payload.clone())
         }
     }
 
+    /// all subscriptions with their listeners.
+    pub fn entries(&self) -> Vec<(Subscription, SharedListener<T>)> {
+        let lock = self.listeners.lock().unwrap();
+        lock.iter()
+            .map(|(subscription, listener)| (subscription.clone(), listener.clone()))
+            .collect()
+    }
+
     #[allow(dead_code)]
     pub fn len(&self) -> usize {
         self.listeners.lock().unwrap().len()
     }
 }
 
+/// Config content delivered to config listeners.
+#[derive(Debug, Clone)]
+pub struct ConfigInfo {
+    pub data_id: String,
+    pub group: String,
+    pub tenant: String,
+    pub content: String,
+    pub md5: String,
+    pub ty: String,
+    /// data key of encrypted content, `None` if the content is not encrypted.
+    pub encrypted_data_key: Option<String>,
+    /// timestamp in milliseconds when the config was last modified on server.
+    pub last_modified: i64,
+}
+
+/// A handle of a registered config listener, the listener is removed when
+/// [ListenerHandle::unsubscribe] is called or the handle is dropped.
+#[must_use = "the listener is removed when the handle is dropped"]
+pub struct ListenerHandle {
+    listeners: ListenerSet<ConfigInfo>,
+    subscription: Option<Subscription>,
+}
+
+impl ListenerHandle {
+    pub(crate) fn new(listeners: ListenerSet<ConfigInfo>, subscription: Subscription) -> Self {
+        ListenerHandle {
+            listeners,
+            subscription: Some(subscription),
+        }
+    }
+
+    /// remove the listener.
+    pub fn unsubscribe(mut self) {
+        self.remove();
+    }
+
+    fn remove(&mut self) {
+        if let Some(subscription) = self.subscription.take() {
+            self.listeners.unsubscribe(subscription);
+        }
+    }
+}
+
+impl Drop for ListenerHandle {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 #[repr(u8)]
 pub enum PropertyChangeType {
@@ -95,16 +163,22 @@ pub trait Listener {
     fn receive_config_info(&self, config_info: Self::Incoming);
 }
 
-pub trait ConnectionEventListener {
-    /// notify when connected to server.
-    fn on_connected(&self);
-    /// notify when disconnected to server.
-    fn on_disconnect(&self);
+pub use nacos_common::common::remote::client::cli::ConnectionEventListener;
+
+/// Event emitted when the server list fetched from address server changes.
+#[derive(Debug, Clone)]
+pub struct ServerListChangedEvent {
+    pub servers: Vec<String>,
+}
+
+pub trait ServerListChangedListener: Send + Sync {
+    /// notify when the server list changed.
+    fn on_changed(&self, event: &ServerListChangedEvent);
 }
 
 #[cfg(test)]
 mod tests {
-    use super::ListenerSet;
+    use super::{ConfigInfo, ListenerHandle, ListenerSet};
     use crate::common::GroupKey;
     use std::sync::mpsc;
 
@@ -117,8 +191,8 @@ mod tests {
     #[test]
     fn test_new_listener_for_chan() {
         let ls = ListenerSet::<bool>::new();
-        let gk = GroupKey::new_without_tanant("001", "pay").unwrap();
-        ls.subscribe(gk, |data| {});
+        let gk = GroupKey::new_without_tenant("001", "pay").unwrap();
+        ls.subscribe(gk, |_data| {});
         assert_eq!(ls.len(), 1);
     }
 
@@ -126,7 +200,7 @@ mod tests {
     fn test_add_listener_to_set() {
         let (tx, rx) = mpsc::channel();
         let ls = ListenerSet::<bool>::new();
-        let gk = GroupKey::new_without_tanant("003", "pay").unwrap();
+        let gk = GroupKey::new_without_tenant("003", "pay").unwrap();
         ls.subscribe(gk, move |e| tx.send(e).unwrap());
         assert_eq!(ls.len(), 1);
 
@@ -138,13 +212,28 @@ mod tests {
     fn test_remove_listener_from_set() {
         let (tx, rx) = mpsc::channel();
         let ls = ListenerSet::<bool>::new();
-        let gk = GroupKey::new_without_tanant("003", "pay").unwrap();
+        let gk = GroupKey::new_without_tenant("003", "pay").unwrap();
         let sub = ls.subscribe(gk, move |e| tx.send(e).unwrap());
         ls.unsubscribe(sub);
         assert_eq!(ls.len(), 0);
         ls.notify(&true);
         assert!(rx.recv().is_err());
     }
+
+    #[test]
+    fn test_drop_listener_handle() {
+        let ls = ListenerSet::<ConfigInfo>::new();
+        let gk = GroupKey::new_without_tenant("003", "pay").unwrap();
+        let sub = ls.subscribe(gk.clone(), |_| {});
+        let handle = ListenerHandle::new(ls.clone(), sub);
+        let sub = ls.subscribe(gk, |_| {});
+        let other = ListenerHandle::new(ls.clone(), sub);
+        assert_eq!(ls.len(), 2);
+        drop(handle);
+        assert_eq!(ls.len(), 1);
+        other.unsubscribe();
+        assert_eq!(ls.len(), 0);
+    }
 }
 
 pub mod config {
@@ -228,9 +317,9 @@ pub mod config {
                     if line.starts_with('#') {
                         continue;
                     }
-                    let (k, v) = line
-                        .split_once('=')
-                        .ok_or(NacosError::msg("properties parse error"))?;
+                    let (k, v) = line.split_once('=').ok_or(NacosError::Serialization(
+                        "properties parse error".to_string(),
+                    ))?;
                     result.insert(k.to_string(), v.to_string());
                 }
             }
@@ -292,7 +381,7 @@ pub mod config {
                 }
             }
 
-            Err(NacosError::msg("Unsupported config type parser."))
+            Err(NacosError::client("Unsupported config type parser."))
         }
     }
 }
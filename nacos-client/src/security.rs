@@ -1,29 +1,87 @@
+use crate::client::cli::{RequestHeaderProvider, RetryPolicy};
+use crate::client::server_list::ServerListManager;
+use crate::credentials::{CredentialsProvider, StaticCredentialsProvider};
 use chrono::Utc;
 use log::{debug, info, warn};
 use nacos_core::error::{NacosError, NacosResult};
+use nacos_proto::grpc::Payload;
 use serde::Deserialize;
+use std::cmp::max;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 pub const LOGIN_URL: &'static str = "/v1/auth/users/login";
 pub const HTTP_PREFIX: &'static str = "http";
 pub const HTTPS_PREFIX: &'static str = "https";
+/// name of the access token in request headers and http query params.
+pub const ACCESS_TOKEN: &'static str = "accessToken";
+/// min interval to refresh token, e.g. to retry a failed login.
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
 
-#[derive(Debug, Clone)]
+#[derive(Clone, Default)]
 pub struct Credentials {
     pub(crate) username: Option<String>,
     pub(crate) password: Option<String>,
+    pub(crate) access_key: Option<String>,
+    pub(crate) secret_key: Option<String>,
+    // token of temporary access key from sts.
+    pub(crate) security_token: Option<String>,
 }
 
 impl Credentials {
+    pub fn new(username: Option<String>, password: Option<String>) -> Self {
+        Credentials {
+            username,
+            password,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_access_key(
+        mut self,
+        access_key: Option<String>,
+        secret_key: Option<String>,
+    ) -> Self {
+        self.access_key = access_key;
+        self.secret_key = secret_key;
+        self
+    }
+
     fn enabled(&self) -> bool {
         self.username.is_some() && !self.username.as_ref().unwrap().trim().is_empty()
     }
+
+    /// access key and secret key to sign requests, `None` unless both are specified.
+    pub(crate) fn access_key_pair(&self) -> Option<(&str, &str)> {
+        let access_key = self.access_key.as_deref()?.trim();
+        let secret_key = self.secret_key.as_deref()?.trim();
+        if access_key.is_empty() || secret_key.is_empty() {
+            return None;
+        }
+        Some((access_key, secret_key))
+    }
+}
+
+/// Secrets are redacted in [Debug] output.
+impl Debug for Credentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let redact = |secret: &Option<String>| secret.as_ref().map(|_| "******");
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .field("password", &redact(&self.password))
+            .field("access_key", &self.access_key)
+            .field("secret_key", &redact(&self.secret_key))
+            .field("security_token", &redact(&self.security_token))
+            .finish()
+    }
 }
 
 #[derive(Debug)]
 pub struct SecurityProxy {
-    // if credentials is none, no auth needed here.
-    credentials: Option<Credentials>,
+    // if credentials provider is none, no auth needed here.
+    credentials_provider: Option<Arc<dyn CredentialsProvider>>,
     access_token: String,
     context_path: String,
     token_ttl: i64,
@@ -34,7 +92,7 @@ pub struct SecurityProxy {
 impl Default for SecurityProxy {
     fn default() -> Self {
         SecurityProxy {
-            credentials: None,
+            credentials_provider: None,
             access_token: "".to_string(),
             context_path: "".to_string(),
             token_ttl: 0,
@@ -44,6 +102,65 @@ impl Default for SecurityProxy {
     }
 }
 
+impl SecurityProxy {
+    /// create a [SecurityProxy] not logged in yet, logging in with the latest credentials of
+    /// `provider`, `context_path` is like `/nacos`.
+    pub fn with_provider(
+        provider: Option<Arc<dyn CredentialsProvider>>,
+        context_path: &str,
+    ) -> Self {
+        SecurityProxy {
+            credentials_provider: provider,
+            context_path: context_path.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// whether credentials are specified, i.e. requests need an access token.
+    pub fn is_enabled(&self) -> bool {
+        self.credentials_provider
+            .as_ref()
+            .map(|provider| provider.credentials().enabled())
+            .unwrap_or(false)
+    }
+
+    /// identity to attach to requests, empty if not logged in.
+    pub fn identity_context(&self) -> HashMap<String, String> {
+        let mut context = HashMap::new();
+        if !self.access_token.is_empty() {
+            context.insert(ACCESS_TOKEN.to_string(), self.access_token.clone());
+        }
+        context
+    }
+
+    /// timestamp in milliseconds to refresh the token, i.e. the refresh window before it expires.
+    fn refresh_time(&self) -> i64 {
+        self.last_refresh_time + (self.token_ttl - self.token_refresh_window) * 1000
+    }
+
+    /// mark the token expired, the next [refresh_login] logs in again.
+    pub fn expire(&mut self) {
+        self.last_refresh_time = 0;
+    }
+
+    /// take the token of `logged_in`, keeping the credentials provider.
+    fn update_token(&mut self, logged_in: SecurityProxy) {
+        self.access_token = logged_in.access_token;
+        self.token_ttl = logged_in.token_ttl;
+        self.last_refresh_time = logged_in.last_refresh_time;
+        self.token_refresh_window = logged_in.token_refresh_window;
+    }
+}
+
+/// Attaches the access token of [SecurityProxy] to every rpc request.
+pub(crate) struct SecurityHeaderProvider(pub(crate) Arc<Mutex<SecurityProxy>>);
+
+impl RequestHeaderProvider for SecurityHeaderProvider {
+    fn headers(&self, _payload: &Payload) -> HashMap<String, String> {
+        self.0.lock().unwrap().identity_context()
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct LoginResponse {
@@ -74,7 +191,7 @@ pub async fn login(
     let token_ttl = result.token_ttl;
     let token_refresh_window = token_ttl / 10;
     Ok(SecurityProxy {
-        credentials: Some(credentials.clone()),
+        credentials_provider: Some(Arc::new(StaticCredentialsProvider(credentials))),
         access_token,
         context_path: context_path.to_string(),
         token_ttl,
@@ -83,16 +200,17 @@ pub async fn login(
     })
 }
 
+/// login to the next server in `server_list`, rotating servers between retries.
 pub async fn refresh_login(
     security_proxy: Arc<Mutex<SecurityProxy>>,
-    server_urls: &Vec<String>,
+    server_list: &ServerListManager,
+    retry_policy: &RetryPolicy,
 ) -> NacosResult<()> {
     // lock to check login state.
     let sp = security_proxy.clone();
     {
         let lock = sp.lock().unwrap();
-        let now = Utc::now().timestamp_millis();
-        if now - lock.last_refresh_time < lock.token_ttl - lock.token_refresh_window {
+        if Utc::now().timestamp_millis() < lock.refresh_time() {
             return Ok(());
         }
     }
@@ -101,36 +219,66 @@ pub async fn refresh_login(
     let (credentials, context_path) = {
         let lock = sp.lock().unwrap();
         (
-            lock.credentials
+            lock.credentials_provider
                 .as_ref()
-                .ok_or(NacosError::msg("no credentials specified."))?
-                .clone(),
+                .ok_or(NacosError::client("no credentials specified."))?
+                .credentials(),
             lock.context_path.as_str().to_string(),
         )
     };
 
     // try to login here.
-    for server in server_urls {
-        let sp = security_proxy.clone();
-        match login(credentials.clone(), server, context_path.clone().as_str()).await {
-            Ok(_sp) => {
-                info!("{:?}", _sp);
-                let mut lock = sp.lock().unwrap();
-                *lock = _sp;
-                return Ok(());
+    let _sp = retry_policy
+        .retry(|_| async {
+            let server = server_list.next_server().ok_or(NacosError::client(format!(
+                "none server to login, server list: {:?}",
+                server_list.server_list()
+            )))?;
+            match login(credentials.clone(), &server, context_path.as_str()).await {
+                Ok(_sp) => {
+                    server_list.record_success(&server);
+                    Ok(_sp)
+                }
+                Err(err) => {
+                    warn!("login to {} failed, {}", server, err);
+                    server_list.record_failure(&server);
+                    Err(err)
+                }
             }
-            Err(err) => {
-                warn!("login failed, {:?}", err);
-                continue;
+        })
+        .await?;
+    info!(
+        "login ok, token ttl is {}s, refresh window is {}s",
+        _sp.token_ttl, _sp.token_refresh_window
+    );
+    let mut lock = security_proxy.lock().unwrap();
+    lock.update_token(_sp);
+    Ok(())
+}
+
+/// refresh token in background before it expires, until `security_proxy` is dropped.
+pub(crate) fn start_refresh(
+    security_proxy: &Arc<Mutex<SecurityProxy>>,
+    server_list: Arc<ServerListManager>,
+    retry_policy: RetryPolicy,
+) {
+    let security_proxy = Arc::downgrade(security_proxy);
+    tokio::spawn(async move {
+        // the proxy is not kept alive while sleeping.
+        while let Some(refresh_time) = security_proxy
+            .upgrade()
+            .map(|security_proxy| security_proxy.lock().unwrap().refresh_time())
+        {
+            let wait = max(refresh_time - Utc::now().timestamp_millis(), 0) as u64;
+            tokio::time::sleep(max(Duration::from_millis(wait), MIN_REFRESH_INTERVAL)).await;
+            if let Some(security_proxy) = security_proxy.upgrade() {
+                if let Err(error) = refresh_login(security_proxy, &server_list, &retry_policy).await
+                {
+                    warn!("refresh access token failed, {}", error);
+                }
             }
         }
-    }
-
-    // none server is in login.
-    Err(NacosError::msg(format!(
-        "none server login success, server list: {:?}",
-        server_urls
-    )))
+    });
 }
 
 #[test]
@@ -141,9 +289,10 @@ fn test_req() {
         let credentials = Credentials {
             username: Some("nacos".to_string()),
             password: Some("nacos".to_string()),
+            ..Default::default()
         };
         let mut security = SecurityProxy {
-            credentials: Some(credentials),
+            credentials_provider: Some(Arc::new(StaticCredentialsProvider(credentials))),
             access_token: "".to_string(),
             context_path: "/nacos".to_string(),
             token_ttl: 0,
@@ -151,11 +300,12 @@ fn test_req() {
             token_refresh_window: 0,
         };
         let sp = Arc::new(Mutex::new(security));
-        let res = refresh_login(sp.clone(), &["127.0.0.1:8848".to_string()].to_vec()).await;
+        let server_list = ServerListManager::new(vec!["127.0.0.1:8848".to_string()]);
+        let res = refresh_login(sp.clone(), &server_list, &RetryPolicy::no_retry()).await;
         if res.is_ok() {
             Ok(sp)
         } else {
-            Err(NacosError::msg("login error."))
+            Err(NacosError::Forbidden("login error.".to_string()))
         }
     };
 
@@ -169,3 +319,34 @@ fn test_req() {
         }
     }
 }
+
+#[test]
+fn test_redact_credentials() {
+    let credentials = Credentials::new(Some("nacos".to_string()), Some("pa55word".to_string()))
+        .with_access_key(Some("ak".to_string()), Some("s3cr3t".to_string()));
+    let debug = format!("{:?}", StaticCredentialsProvider(credentials));
+    assert!(debug.contains("nacos") && debug.contains("ak"));
+    assert!(!debug.contains("pa55word") && !debug.contains("s3cr3t"));
+}
+
+#[test]
+fn test_identity_context() {
+    let credentials = Credentials::new(Some("nacos".to_string()), Some("nacos".to_string()));
+    let provider = Arc::new(StaticCredentialsProvider(credentials));
+    let mut security = SecurityProxy::with_provider(Some(provider), "/nacos");
+    assert!(security.is_enabled());
+    assert!(security.identity_context().is_empty());
+    security.access_token = "token".to_string();
+    security.token_ttl = 18000;
+    security.token_refresh_window = 1800;
+    security.last_refresh_time = Utc::now().timestamp_millis();
+    assert_eq!(security.identity_context()[ACCESS_TOKEN], "token");
+    // refresh in the window before the token expires, ttl is in seconds.
+    assert_eq!(
+        security.refresh_time() - security.last_refresh_time,
+        16200 * 1000
+    );
+    security.expire();
+    assert!(security.refresh_time() < Utc::now().timestamp_millis());
+    assert!(!SecurityProxy::default().is_enabled());
+}
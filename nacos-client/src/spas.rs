@@ -0,0 +1,195 @@
+//! A module to sign requests with access key and secret key by the spas scheme of ACM.
+use crate::client::cli::RequestHeaderProvider;
+use crate::credentials::CredentialsProvider;
+use crate::crypto::sign_with_hmac_sha1;
+use chrono::Utc;
+use nacos_proto::grpc::Payload;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub const SPAS_ACCESS_KEY: &'static str = "Spas-AccessKey";
+pub const SPAS_SIGNATURE: &'static str = "Spas-Signature";
+pub const TIMESTAMP: &'static str = "Timestamp";
+pub const SPAS_SECURITY_TOKEN: &'static str = "Spas-SecurityToken";
+/// splitter of group and service in a grouped service name, e.g. `DEFAULT_GROUP@@service`.
+const SERVICE_INFO_SPLITTER: &'static str = "@@";
+
+/// Module of requests to sign, config and naming sign different resources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignModule {
+    Config,
+    // signed by the naming client, which is not in this crate yet.
+    #[allow(dead_code)]
+    Naming,
+}
+
+/// Signs every request with HMAC-SHA1 of the secret key, config requests sign
+/// `tenant+group+timestamp` and naming requests sign `timestamp@@serviceName`.
+/// Requests are not signed while the provider has no access key and secret key.
+#[derive(Debug, Clone)]
+pub struct SpasSigner {
+    provider: Arc<dyn CredentialsProvider>,
+    module: SignModule,
+}
+
+impl SpasSigner {
+    /// create a [SpasSigner] signing with the latest access key and secret key of `provider`.
+    pub fn with_provider(provider: Arc<dyn CredentialsProvider>, module: SignModule) -> Self {
+        SpasSigner { provider, module }
+    }
+
+    /// headers to sign `payload` at `timestamp` in milliseconds.
+    pub fn sign_headers(&self, payload: &Payload, timestamp: i64) -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+        let credentials = self.provider.credentials();
+        let (access_key, secret_key) = match credentials.access_key_pair() {
+            Some(pair) => pair,
+            None => return headers,
+        };
+        let body = payload
+            .body
+            .as_ref()
+            .and_then(|body| serde_json::from_slice::<Value>(&body.value).ok())
+            .unwrap_or(Value::Null);
+        let data = match self.module {
+            SignModule::Config => config_sign_data(
+                body["tenant"].as_str().unwrap_or(""),
+                body["group"].as_str().unwrap_or(""),
+                timestamp,
+            ),
+            SignModule::Naming => naming_sign_data(
+                &grouped_service_name(
+                    body["serviceName"].as_str().unwrap_or(""),
+                    body["groupName"].as_str().unwrap_or(""),
+                ),
+                timestamp,
+            ),
+        };
+        headers.insert(SPAS_ACCESS_KEY.to_string(), access_key.to_string());
+        headers.insert(
+            SPAS_SIGNATURE.to_string(),
+            sign_with_hmac_sha1(data.as_str(), secret_key),
+        );
+        headers.insert(TIMESTAMP.to_string(), timestamp.to_string());
+        if let Some(security_token) = &credentials.security_token {
+            headers.insert(SPAS_SECURITY_TOKEN.to_string(), security_token.clone());
+        }
+        headers
+    }
+}
+
+impl RequestHeaderProvider for SpasSigner {
+    fn headers(&self, payload: &Payload) -> HashMap<String, String> {
+        self.sign_headers(payload, Utc::now().timestamp_millis())
+    }
+}
+
+/// `tenant+group+timestamp`, blank tenant and group are left out.
+fn config_sign_data(tenant: &str, group: &str, timestamp: i64) -> String {
+    let (tenant, group) = (tenant.trim(), group.trim());
+    if !tenant.is_empty() && !group.is_empty() {
+        format!("{}+{}+{}", tenant, group, timestamp)
+    } else if !group.is_empty() {
+        format!("{}+{}", group, timestamp)
+    } else {
+        timestamp.to_string()
+    }
+}
+
+/// `timestamp@@serviceName`, or `timestamp` if the service is blank.
+fn naming_sign_data(service_name: &str, timestamp: i64) -> String {
+    if service_name.trim().is_empty() {
+        timestamp.to_string()
+    } else {
+        format!("{}{}{}", timestamp, SERVICE_INFO_SPLITTER, service_name)
+    }
+}
+
+fn grouped_service_name(service_name: &str, group_name: &str) -> String {
+    if service_name.is_empty() || group_name.is_empty() {
+        service_name.to_string()
+    } else {
+        format!("{}{}{}", group_name, SERVICE_INFO_SPLITTER, service_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        SignModule, SpasSigner, SPAS_ACCESS_KEY, SPAS_SECURITY_TOKEN, SPAS_SIGNATURE, TIMESTAMP,
+    };
+    use crate::credentials::StaticCredentialsProvider;
+    use crate::crypto::sign_with_hmac_sha1;
+    use crate::security::Credentials;
+    use nacos_api::api::remote::request::ConfigQueryRequest;
+    use nacos_common::common::remote::client::grpc::utils::convert_request;
+    use nacos_proto::grpc::{Metadata, Payload};
+    use prost_types::Any;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+
+    fn signer(module: SignModule) -> SpasSigner {
+        let credentials =
+            Credentials::default().with_access_key(Some("ak".to_string()), Some("sk".to_string()));
+        SpasSigner::with_provider(Arc::new(StaticCredentialsProvider(credentials)), module)
+    }
+
+    fn json_payload(body: Value) -> Payload {
+        Payload {
+            metadata: Some(Metadata::default()),
+            body: Some(Any {
+                type_url: "".to_string(),
+                value: serde_json::to_vec(&body).unwrap(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_sign_config_request() {
+        let signer = signer(SignModule::Config);
+        let request = ConfigQueryRequest::new(
+            "app".to_string(),
+            "DEFAULT_GROUP".to_string(),
+            Some("dev".to_string()),
+        );
+        let headers = signer.sign_headers(&convert_request(&request), 1000);
+        assert_eq!(headers[SPAS_ACCESS_KEY], "ak");
+        assert_eq!(headers[TIMESTAMP], "1000");
+        assert_eq!(
+            headers[SPAS_SIGNATURE],
+            sign_with_hmac_sha1("dev+DEFAULT_GROUP+1000", "sk")
+        );
+        // requests without group sign the timestamp only.
+        let headers = signer.sign_headers(&json_payload(json!({})), 1000);
+        assert_eq!(headers[SPAS_SIGNATURE], sign_with_hmac_sha1("1000", "sk"));
+    }
+
+    #[test]
+    fn test_sign_with_provider() {
+        let mut credentials = Credentials::new(Some("nacos".to_string()), None);
+        let provider = Arc::new(StaticCredentialsProvider(credentials.clone()));
+        let signer = SpasSigner::with_provider(provider, SignModule::Config);
+        // not signed without access key.
+        assert!(signer
+            .sign_headers(&json_payload(json!({})), 1000)
+            .is_empty());
+        credentials = credentials.with_access_key(Some("ak".to_string()), Some("sk".to_string()));
+        credentials.security_token = Some("token".to_string());
+        let provider = Arc::new(StaticCredentialsProvider(credentials));
+        let signer = SpasSigner::with_provider(provider, SignModule::Config);
+        let headers = signer.sign_headers(&json_payload(json!({})), 1000);
+        assert_eq!(headers[SPAS_SECURITY_TOKEN], "token");
+    }
+
+    #[test]
+    fn test_sign_naming_request() {
+        let signer = signer(SignModule::Naming);
+        let payload = json_payload(json!({"serviceName": "service", "groupName": "DEFAULT_GROUP"}));
+        let headers = signer.sign_headers(&payload, 1000);
+        assert_eq!(
+            headers[SPAS_SIGNATURE],
+            sign_with_hmac_sha1("1000@@DEFAULT_GROUP@@service", "sk")
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use nacos_core::error::NacosResult;
+use nacos_core::error::{NacosError, NacosResult};
 use serde::Deserialize;
 use toml::{Config, Environment, File};
 
@@ -6,12 +6,15 @@ use toml::{Config, Environment, File};
 pub fn read_toml_from_resources<'de, T: Deserialize<'de>>(prefix: &str) -> NacosResult<T> {
     let mut s = Config::default();
     let default = format!("resources/{}.toml", prefix);
-    s.merge(File::with_name(default.as_str()))?;
+    s.merge(File::with_name(default.as_str()))
+        .map_err(NacosError::client)?;
     // config environment conf file.
     let env = std::env::var("RUN_MODE").unwrap_or(String::from("dev"));
     let file_name = format!("resources/{}-{}.toml", prefix, env);
-    s.merge(File::with_name(file_name.as_str()))?;
+    s.merge(File::with_name(file_name.as_str()))
+        .map_err(NacosError::client)?;
     // from environment
-    s.merge(Environment::with_prefix(prefix))?;
-    Ok(s.try_into()?)
+    s.merge(Environment::with_prefix(prefix))
+        .map_err(NacosError::client)?;
+    s.try_into().map_err(NacosError::client)
 }
@@ -1,11 +1,37 @@
-use crate::common::remote::client::conn::ServerInfo;
+//! A module to handle RpcClient.
+use crate::common::remote::client::conn::{rpc_port_offset, ConnectionMeta, ServerInfo};
+use crate::common::remote::client::grpc::grpc_conn::{GrpcConnection, PendingRequests};
+use crate::common::remote::client::grpc::utils::{
+    convert_request, local_ip_address, parse_response,
+};
+use crate::common::remote::client::handlers::{
+    ClientDetectionHandler, ConnectResetHandler, ServerRequestHandler, ServerRequestHandlers,
+};
+use crate::common::remote::client::retry::RetryPolicy;
 use chrono::Local;
+use nacos_api::api::ability::env::get_env;
+use nacos_api::api::ability::ClientAbilities;
+use nacos_api::api::remote::request::{
+    ConnectionSetupRequest, HealthCheckRequest, ServerCheckRequest,
+};
+use nacos_api::api::remote::response::{HealthCheckResponse, ServerCheckResponse};
 use nacos_api::api::traits::{RequestExt, ResponseExt};
+use nacos_core::error::{NacosError, NacosResult};
+use nacos_proto::grpc::bi_request_stream_client::BiRequestStreamClient;
+use nacos_proto::grpc::request_client::RequestClient;
+use nacos_proto::grpc::Payload;
+use nacos_proto::log_payload;
 use serde::{Deserialize, Serialize};
-use std::io::Bytes;
-use std::ops::Deref;
-use std::sync::atomic::{AtomicI64, AtomicIsize};
-use std::sync::{Arc, Mutex};
+use std::cmp::{max, min};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::mpsc::{self, Sender};
+use tonic::transport::{Channel, Uri};
+use tonic::Request;
+
 pub const CONNECTED: isize = 1;
 pub const DISCONNECTED: isize = 0;
 #[derive(Serialize, Deserialize)]
@@ -25,77 +51,888 @@ impl ConnectionEvent {
         self.event_type == DISCONNECTED
     }
 }
+/// Context of a reconnect, `server_info` is the recommended server to switch to.
 pub struct ReconnectContext {
-    pub server_info: ServerInfo,
+    pub server_info: Option<ServerInfo>,
     pub on_request_fail: bool,
 }
+
+impl ReconnectContext {
+    pub fn new(server_info: Option<ServerInfo>, on_request_fail: bool) -> Self {
+        ReconnectContext {
+            server_info,
+            on_request_fail,
+        }
+    }
+}
+/// resolve address like `127.0.0.1:8848` or `https://127.0.0.1:8848` to [ServerInfo],
+/// panics if the address is malformed, see [try_resolve_server_info].
 pub fn resolve_server_info(server_address: String) -> ServerInfo {
-    const HTTP_PREFIX: &'static str = "http";
-    const COLON: &'static str = ":";
-    let server_port = std::env::var("nacos.server.port").unwrap_or("8848".to_string());
-    let mut server_port = server_port.parse::<u16>().unwrap();
-    if server_address.contains(HTTP_PREFIX) {
-        let mut s = server_address.split(COLON);
-        let _ = s.next().unwrap();
-        let ip_address = s.next().unwrap().replace("//", "");
-        if let Some(port) = s.next() {
-            server_port = port.parse::<u16>().unwrap();
-        }
-        ServerInfo {
-            server_ip: ip_address,
-            server_port,
+    try_resolve_server_info(&server_address).unwrap()
+}
+
+/// resolve address like `127.0.0.1:8848` or `https://127.0.0.1:8848` to [ServerInfo],
+/// the port is `nacos.server.port` env or `8848` if not specified.
+pub fn try_resolve_server_info(server_address: &str) -> NacosResult<ServerInfo> {
+    const HTTP_PREFIX: &'static str = "http://";
+    const HTTPS_PREFIX: &'static str = "https://";
+    let invalid = || NacosError::client(format!("invalid server address `{}`", server_address));
+    let address = server_address.trim();
+    let (address, enable_ssl) = match address.strip_prefix(HTTPS_PREFIX) {
+        Some(address) => (address, true),
+        None => (address.strip_prefix(HTTP_PREFIX).unwrap_or(address), false),
+    };
+    let address = address.trim_end_matches('/');
+    let (ip, port) = match address.split_once(':') {
+        Some((ip, port)) => (ip, Some(port)),
+        None => (address, None),
+    };
+    if ip.is_empty() || ip.contains('/') {
+        return Err(invalid());
+    }
+    let server_port = match port {
+        Some(port) => port.parse::<u16>().map_err(|_| invalid())?,
+        None => std::env::var("nacos.server.port")
+            .ok()
+            .and_then(|port| port.parse::<u16>().ok())
+            .unwrap_or(8848),
+    };
+    let mut server_info = ServerInfo::new(ip.to_string(), server_port);
+    server_info.enable_ssl = enable_ssl;
+    Ok(server_info)
+}
+pub trait ConnectionEventListener: Send + Sync {
+    /// notify when connected to server.
+    fn on_connected(&self);
+    /// notify when disconnected to server.
+    fn on_disconnect(&self);
+}
+
+/// Source of headers attached to every request sent by [RpcClient::request],
+/// e.g. the access token of an auth-enabled cluster.
+pub trait RequestHeaderProvider: Send + Sync {
+    /// headers to attach to `payload`.
+    fn headers(&self, payload: &Payload) -> HashMap<String, String>;
+}
+
+/// Source of servers to connect, addresses are like `127.0.0.1:8848`.
+pub trait ServerListFactory: Send + Sync {
+    /// the next server to connect in rotation.
+    fn next_server(&self) -> Option<String>;
+    /// all servers.
+    fn server_list(&self) -> Vec<String>;
+    /// record a failure of connecting to the server.
+    fn record_failure(&self, _server: &str) {}
+    /// record a success of connecting to the server.
+    fn record_success(&self, _server: &str) {}
+}
+
+/// a health check is sent if nothing is received from server in this window, in milliseconds.
+const KEEP_ALIVE_TIME: u64 = 5000;
+/// consecutive failures of health check before the connection is marked unhealthy.
+const MAX_HEALTH_CHECK_FAILURES: u32 = 3;
+const HEALTH_CHECK_TIMEOUT: u64 = 3000;
+const CLIENT_VERSION: &'static str = "Nacos-Rust-Sdk-0.1.0";
+const CONNECT_TYPE_GRPC: &'static str = "GRPC";
+/// max interval between reconnect rounds.
+const MAX_RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Lifecycle state of [RpcClient].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientState {
+    /// the client is created but not connected yet.
+    Starting,
+    /// the client is connected to a server.
+    Running,
+    /// the connection is lost, the client is reconnecting.
+    Unhealthy,
+    /// the client is shut down, it never reconnects.
+    Shutdown,
+}
+
+pub struct RpcClient {
+    connection: RwLock<Option<Arc<GrpcConnection>>>,
+    state: Mutex<ClientState>,
+    server_list: RwLock<Option<Arc<dyn ServerListFactory>>>,
+    reconnect_sender: mpsc::Sender<ReconnectContext>,
+    reconnect_receiver: Mutex<Option<mpsc::Receiver<ReconnectContext>>>,
+    pub tenant: Option<String>,
+    pub client_abilities: ClientAbilities,
+    pub labels: HashMap<String, String>,
+    /// timestamp in milliseconds when the client heard from server lastly.
+    pub last_active_timestamp: Arc<AtomicU64>,
+    pub server_request_handlers: ServerRequestHandlers,
+    /// policy to retry failed requests sent by [RpcClient::request].
+    pub retry_policy: RetryPolicy,
+    connection_event_listeners: RwLock<Vec<Box<dyn ConnectionEventListener>>>,
+    header_providers: RwLock<Vec<Box<dyn RequestHeaderProvider>>>,
+}
+
+impl RpcClient {
+    /// create a [RpcClient] without any connection, `labels` tell server the module of client,
+    /// e.g. config or naming.
+    pub fn new(tenant: Option<String>, labels: HashMap<String, String>) -> Self {
+        let (reconnect_sender, reconnect_receiver) = mpsc::channel(1);
+        RpcClient {
+            connection: RwLock::new(None),
+            state: Mutex::new(ClientState::Starting),
+            server_list: RwLock::new(None),
+            reconnect_sender,
+            reconnect_receiver: Mutex::new(Some(reconnect_receiver)),
+            tenant,
+            client_abilities: Default::default(),
+            labels,
+            last_active_timestamp: Arc::new(AtomicU64::new(now_millis())),
+            server_request_handlers: Default::default(),
+            retry_policy: Default::default(),
+            connection_event_listeners: RwLock::new(vec![]),
+            header_providers: RwLock::new(vec![]),
         }
-    } else {
-        let mut s = server_address.split(COLON);
-        let ip = s.next().unwrap();
-        if let Some(port) = s.next() {
-            server_port = port.parse().unwrap();
+    }
+
+    /// register a handler of requests pushed by server.
+    pub fn register_server_request_handler<H>(&self, handler: H)
+    where
+        H: ServerRequestHandler,
+    {
+        self.server_request_handlers.register(handler);
+    }
+
+    /// register a listener notified when the client connects to or disconnects from server.
+    pub fn register_connection_event_listener<L>(&self, listener: L)
+    where
+        L: ConnectionEventListener + 'static,
+    {
+        let mut lock = self.connection_event_listeners.write().unwrap();
+        lock.push(Box::new(listener));
+    }
+
+    /// register a provider of headers attached to every request.
+    pub fn register_header_provider<P>(&self, provider: P)
+    where
+        P: RequestHeaderProvider + 'static,
+    {
+        let mut lock = self.header_providers.write().unwrap();
+        lock.push(Box::new(provider));
+    }
+
+    /// attach headers of providers to `payload`, they may change between attempts.
+    fn attach_headers(&self, payload: &mut Payload) {
+        let providers = self.header_providers.read().unwrap();
+        for provider in providers.iter() {
+            let headers = provider.headers(payload);
+            if let Some(metadata) = payload.metadata.as_mut() {
+                metadata.headers.extend(headers);
+            }
+        }
+    }
+
+    pub fn state(&self) -> ClientState {
+        *self.state.lock().unwrap()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.state() == ClientState::Running
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.state() == ClientState::Shutdown
+    }
+
+    /// the connection in use, fails if the client is not connected.
+    pub fn current_connection(&self) -> NacosResult<Arc<GrpcConnection>> {
+        self.connection
+            .read()
+            .unwrap()
+            .clone()
+            .ok_or(NacosError::Disconnected(
+                "client is not connected to any server".to_string(),
+            ))
+    }
+
+    /// connect to the first available server in `server_list` and keep reconnecting in background
+    /// once the connection is lost.
+    pub async fn start(
+        self: &Arc<Self>,
+        server_list: Arc<dyn ServerListFactory>,
+    ) -> NacosResult<()> {
+        let receiver = self.reconnect_receiver.lock().unwrap().take();
+        let receiver = match receiver {
+            Some(receiver) => receiver,
+            None => return Err(NacosError::client("RpcClient is already started")),
+        };
+        *self.server_list.write().unwrap() = Some(server_list.clone());
+        self.register_server_request_handler(ConnectResetHandler(Arc::downgrade(self)));
+        self.register_server_request_handler(ClientDetectionHandler(Arc::downgrade(self)));
+        for _ in 0..server_list.server_list().len() {
+            let server_info = match next_server_info(server_list.as_ref()) {
+                Some(server_info) => server_info,
+                None => break,
+            };
+            if let Some(connection) = self.try_connect(server_info).await {
+                self.switch_connection(connection);
+                tokio::spawn(self.clone().reconnect_loop(receiver));
+                tokio::spawn(self.clone().keep_alive_loop());
+                return Ok(());
+            }
         }
+        *self.reconnect_receiver.lock().unwrap() = Some(receiver);
+        Err(NacosError::Disconnected(format!(
+            "none server is available, server list: {:?}",
+            server_list.server_list()
+        )))
+    }
+
+    /// connect to `server_info` and record the result in server list.
+    async fn try_connect(&self, server_info: ServerInfo) -> Option<GrpcConnection> {
+        let server_list = self.server_list.read().unwrap().clone();
+        let address = server_info.address();
+        match self.connect_to_server(server_info).await {
+            Ok(connection) => {
+                log::info!("connect to server {} successfully", address);
+                if let Some(server_list) = server_list {
+                    server_list.record_success(address.as_str());
+                }
+                Some(connection)
+            }
+            Err(error) => {
+                log::warn!("connect to server {} failed, {}", address, error);
+                if let Some(server_list) = server_list {
+                    server_list.record_failure(address.as_str());
+                }
+                None
+            }
+        }
+    }
 
-        ServerInfo {
-            server_ip: ip.to_string(),
-            server_port,
+    /// switch server if the one in use is removed from server list.
+    pub fn on_server_list_changed(&self, servers: &[String]) {
+        if let Ok(connection) = self.current_connection() {
+            let address = connection.server_info.address();
+            if !servers.contains(&address) {
+                log::info!(
+                    "server {} is removed from server list, switch server.",
+                    address
+                );
+                self.switch_server_async(None, false);
+            }
+        }
+    }
+
+    /// send `request` through the connection in use and parse the response, failed requests
+    /// are retried by [RpcClient::retry_policy] on the next server.
+    pub async fn request<Req, Resp>(&self, request: &Req, timeout_millis: u64) -> NacosResult<Resp>
+    where
+        Req: RequestExt,
+        Resp: ResponseExt + 'static,
+    {
+        self.request_with_policy(request, timeout_millis, &self.retry_policy)
+            .await
+    }
+
+    /// send `request` like [RpcClient::request] but retried by `retry_policy`, e.g. a request
+    /// not safe to send twice is never retried.
+    pub async fn request_with_policy<Req, Resp>(
+        &self,
+        request: &Req,
+        timeout_millis: u64,
+        retry_policy: &RetryPolicy,
+    ) -> NacosResult<Resp>
+    where
+        Req: RequestExt,
+        Resp: ResponseExt + 'static,
+    {
+        let payload = convert_request(request);
+        // the connection the last attempt failed on.
+        let failed = Mutex::new(None);
+        retry_policy
+            .retry(|attempts| {
+                let mut payload = payload.clone();
+                self.attach_headers(&mut payload);
+                let failed = &failed;
+                async move {
+                    if attempts > 0 {
+                        let connection = failed.lock().unwrap().take();
+                        self.rotate_server(connection).await;
+                    }
+                    let connection = self.current_connection()?;
+                    let result = match connection.request_payload(payload, timeout_millis).await {
+                        Ok(response) => parse_response::<Resp>(&response),
+                        Err(error) => Err(error),
+                    };
+                    if result.is_err() {
+                        *failed.lock().unwrap() = Some(connection);
+                    }
+                    result
+                }
+            })
+            .await
+    }
+
+    /// connect to the next server after a request failed on `failed`, the connection in use
+    /// is kept if it is switched already or the next server is not available.
+    async fn rotate_server(&self, failed: Option<Arc<GrpcConnection>>) {
+        if self.is_shutdown() {
+            return;
+        }
+        let current = self.connection.read().unwrap().clone();
+        if let (Some(failed), Some(current)) = (failed.as_ref(), current.as_ref()) {
+            if !Arc::ptr_eq(failed, current) {
+                return;
+            }
+        }
+        let server_list = self.server_list.read().unwrap().clone();
+        let server_info =
+            match server_list.and_then(|server_list| next_server_info(server_list.as_ref())) {
+                Some(server_info) => server_info,
+                None => return,
+            };
+        let in_use = current
+            .map(|current| current.server_info.address() == server_info.address())
+            .unwrap_or(false);
+        // nothing to rotate if the only server is in use.
+        if in_use && self.is_running() {
+            return;
+        }
+        log::info!("request failed, switch to server {}", server_info.address());
+        if let Some(connection) = self.try_connect(server_info).await {
+            self.mark_unhealthy();
+            self.switch_connection(connection);
+        }
+    }
+
+    /// switch to the recommended server or the next server in background.
+    pub fn switch_server_async(&self, server_info: Option<ServerInfo>, on_request_fail: bool) {
+        let context = ReconnectContext::new(server_info, on_request_fail);
+        // a pending reconnect is enough if the channel is full.
+        let _ = self.reconnect_sender.try_send(context);
+    }
+
+    pub fn shutdown(&self) {
+        *self.state.lock().unwrap() = ClientState::Shutdown;
+        if let Some(connection) = self.connection.write().unwrap().take() {
+            connection.abandon();
+        }
+        // wake up the reconnect loop to quit.
+        let _ = self
+            .reconnect_sender
+            .try_send(ReconnectContext::new(None, false));
+        log::info!("RpcClient shutdown successfully.");
+    }
+
+    async fn reconnect_loop(self: Arc<Self>, mut receiver: mpsc::Receiver<ReconnectContext>) {
+        while let Some(context) = receiver.recv().await {
+            if self.is_shutdown() {
+                break;
+            }
+            self.reconnect(context).await;
+        }
+        log::info!("RpcClient reconnect loop quit.");
+    }
+
+    /// send health check to server whenever the connection is idle longer than [KEEP_ALIVE_TIME].
+    async fn keep_alive_loop(self: Arc<Self>) {
+        let mut failures = 0;
+        loop {
+            tokio::time::sleep(Duration::from_millis(KEEP_ALIVE_TIME / 5)).await;
+            if self.is_shutdown() {
+                break;
+            }
+            if !self.is_running() {
+                failures = 0;
+                continue;
+            }
+            let idle =
+                now_millis().saturating_sub(self.last_active_timestamp.load(Ordering::SeqCst));
+            if idle < KEEP_ALIVE_TIME {
+                continue;
+            }
+            match self.health_check().await {
+                Ok(()) => failures = 0,
+                Err(error) => {
+                    failures += 1;
+                    log::warn!("health check failed {} times, {}", failures, error);
+                    if failures >= MAX_HEALTH_CHECK_FAILURES {
+                        failures = 0;
+                        self.mark_unhealthy();
+                        self.switch_server_async(None, false);
+                    }
+                }
+            }
         }
     }
+
+    /// send a [HealthCheckRequest] through the connection in use.
+    pub async fn health_check(&self) -> NacosResult<()> {
+        let connection = self.current_connection()?;
+        let payload = connection
+            .request_timeout(HealthCheckRequest::new(), HEALTH_CHECK_TIMEOUT)
+            .await?;
+        let response = parse_response::<HealthCheckResponse>(&payload)?;
+        if !response.is_success() {
+            return Err(NacosError::server(
+                response.error_code as i32,
+                format!(
+                    "health check fail, msg={}",
+                    response.message.as_deref().unwrap_or("")
+                ),
+            ));
+        }
+        self.last_active_timestamp
+            .store(now_millis(), Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// mark the client unhealthy, listeners are notified if it was running.
+    fn mark_unhealthy(&self) {
+        let was_running = {
+            let mut state = self.state.lock().unwrap();
+            let was_running = *state == ClientState::Running;
+            if *state != ClientState::Shutdown {
+                *state = ClientState::Unhealthy;
+            }
+            was_running
+        };
+        if was_running {
+            self.notify_disconnected();
+        }
+    }
+
+    async fn reconnect(&self, context: ReconnectContext) {
+        // the request may fail for reasons other than the connection.
+        if context.on_request_fail && self.is_running() && self.health_check().await.is_ok() {
+            return;
+        }
+        self.mark_unhealthy();
+        let mut recommended = context.server_info;
+        let mut round = 0u32;
+        loop {
+            let server_list = self.server_list.read().unwrap().clone();
+            let server_list_size = server_list
+                .as_ref()
+                .map(|server_list| server_list.server_list().len())
+                .unwrap_or(0);
+            for _ in 0..max(server_list_size, 1) {
+                if self.is_shutdown() {
+                    return;
+                }
+                let server_info = match recommended.take() {
+                    Some(server_info) => server_info,
+                    None => match server_list
+                        .as_ref()
+                        .and_then(|server_list| next_server_info(server_list.as_ref()))
+                    {
+                        Some(server_info) => server_info,
+                        None => break,
+                    },
+                };
+                if let Some(connection) = self.try_connect(server_info).await {
+                    self.switch_connection(connection);
+                    return;
+                }
+            }
+            round += 1;
+            let interval = Duration::from_millis(100 * round as u64);
+            tokio::time::sleep(min(interval, MAX_RECONNECT_INTERVAL)).await;
+        }
+    }
+
+    /// replace the connection in use with `connection` and mark the client running.
+    fn switch_connection(&self, connection: GrpcConnection) {
+        {
+            let mut state = self.state.lock().unwrap();
+            if *state == ClientState::Shutdown {
+                connection.abandon();
+                return;
+            }
+            let old = self
+                .connection
+                .write()
+                .unwrap()
+                .replace(Arc::new(connection));
+            if let Some(old) = old {
+                old.abandon();
+            }
+            *state = ClientState::Running;
+        }
+        self.last_active_timestamp
+            .store(now_millis(), Ordering::SeqCst);
+        self.notify_connected();
+    }
+
+    fn notify_connected(&self) {
+        for listener in self.connection_event_listeners.read().unwrap().iter() {
+            listener.on_connected();
+        }
+    }
+
+    fn notify_disconnected(&self) {
+        for listener in self.connection_event_listeners.read().unwrap().iter() {
+            listener.on_disconnect();
+        }
+    }
+
+    pub async fn connect_to_server(&self, server_info: ServerInfo) -> NacosResult<GrpcConnection> {
+        let channel = create_new_channel(&server_info).await?;
+        let stub = RequestClient::new(channel.clone());
+        // server check
+        let connection_id = match server_check(stub).await {
+            Ok(response) => response.connection_id,
+            Err(error) => {
+                log::error!("server check error, {}", error);
+                return Err(error);
+            }
+        };
+        // bind bi config stream
+        let meta_info = self.connection_meta(&server_info, connection_id.as_str());
+        let mut grpc_conn = GrpcConnection::new(server_info);
+        grpc_conn.connection_id = Some(connection_id);
+        grpc_conn.connection.get_mut().unwrap().meta_info = Some(meta_info);
+        let bi_request_stream_stub = self
+            .bind_request_stream(
+                &channel,
+                grpc_conn.abandon.clone(),
+                grpc_conn.pending_requests.clone(),
+            )
+            .await?;
+        grpc_conn.sender = Some(bi_request_stream_stub);
+        grpc_conn.channel = Some((&channel).clone());
+        grpc_conn.request_stub = Some(RequestClient::new(channel.clone()));
+        let connection_setup_request = ConnectionSetupRequest {
+            inner: Default::default(),
+            client_version: CLIENT_VERSION.to_string(),
+            abilities: self.client_abilities.clone(),
+            tenant: self.tenant.as_ref().unwrap_or(&"".to_string()).to_string(),
+            labels: self.labels.clone(),
+        };
+        grpc_conn.send_request(connection_setup_request).await?;
+        Ok(grpc_conn)
+    }
+
+    fn connection_meta(&self, server_info: &ServerInfo, connection_id: &str) -> ConnectionMeta {
+        let now = Local::now();
+        ConnectionMeta {
+            connect_type: CONNECT_TYPE_GRPC.to_string(),
+            client_ip: local_ip_address(),
+            remote_ip: server_info.server_ip.clone(),
+            remote_port: server_info.server_port + rpc_port_offset(),
+            local_port: 0,
+            version: CLIENT_VERSION.to_string(),
+            connection_id: Some(connection_id.to_string()),
+            create_time: now,
+            last_active_time: now.timestamp() as u64,
+            app_name: get_env("APP_NAME", "unknown"),
+            tenant: self.tenant.clone(),
+            labels: self.labels.clone(),
+        }
+    }
+
+    async fn bind_request_stream(
+        &self,
+        channel: &Channel,
+        abandon: Arc<AtomicBool>,
+        pending_requests: PendingRequests,
+    ) -> NacosResult<Sender<Payload>> {
+        let mut bi = BiRequestStreamClient::new(channel.clone());
+        let (sender, mut receiver) = mpsc::channel::<Payload>(1024);
+        // send a setup config.
+        let outbound = async_stream::stream! {
+            while let Some(payload) = receiver.recv().await {
+                log_payload(&payload);
+                yield payload;
+            }
+        };
+        let mut request = Request::new(outbound);
+        request.set_timeout(Duration::from_secs(15000));
+        match bi.request_bi_stream(request).await {
+            Ok(response_stream) => {
+                let mut streaming = response_stream.into_inner();
+                let responder = sender.clone();
+                let handlers = self.server_request_handlers.clone();
+                let reconnect_sender = self.reconnect_sender.clone();
+                let last_active_timestamp = self.last_active_timestamp.clone();
+                tokio::spawn(async move {
+                    loop {
+                        let payload = match streaming.message().await {
+                            Ok(Some(payload)) => payload,
+                            Ok(None) => {
+                                log::warn!("bi stream is closed by server.");
+                                break;
+                            }
+                            Err(error) => {
+                                log::warn!("bi stream error, {}", error);
+                                break;
+                            }
+                        };
+                        log_payload(&payload);
+                        last_active_timestamp.store(now_millis(), Ordering::SeqCst);
+                        // responses of requests sent over the bi-stream.
+                        let payload = match pending_requests.complete(payload) {
+                            Some(payload) => payload,
+                            None => continue,
+                        };
+                        let handlers = handlers.clone();
+                        let responder = responder.clone();
+                        tokio::spawn(async move {
+                            // send response to server.
+                            if let Some(response) = handlers.dispatch(&payload).await {
+                                if responder.send(response).await.is_err() {
+                                    log::warn!("send response of server request error.");
+                                }
+                            }
+                        });
+                    }
+                    pending_requests.clear();
+                    // the stream of a connection in use ends, switch to another server.
+                    if !abandon.load(Ordering::SeqCst) {
+                        let _ = reconnect_sender.try_send(ReconnectContext::new(None, false));
+                    }
+                });
+            }
+            Err(error) => {
+                log::error!("bi stream request error : {}", error);
+                // the connection is useless without bi stream, try another server.
+                return Err(NacosError::Disconnected(format!(
+                    "bi stream request error, {}",
+                    error
+                )));
+            }
+        }
+        Ok(sender)
+    }
 }
-type ServerRequestHandler = Box<dyn Fn(String) -> Option<String> + Send + 'static>;
-struct ServerRequestHandlerSet {
-    handlers: Arc<Mutex<Vec<ServerRequestHandler>>>,
+
+async fn server_check(
+    request_blocking_stub: RequestClient<Channel>,
+) -> NacosResult<ServerCheckResponse> {
+    let mut request_blocking_stub = request_blocking_stub;
+    let server_check_request = ServerCheckRequest::new();
+    let payload_request = convert_request::<ServerCheckRequest>(&server_check_request);
+    let mut request = Request::new(payload_request);
+    request.set_timeout(Duration::from_millis(3000));
+    let mut response = request_blocking_stub.request(request).await?;
+    let payload = response.into_inner();
+    log_payload(&payload);
+    let response = parse_response::<ServerCheckResponse>(&payload)?;
+    Ok(response)
 }
-impl ServerRequestHandlerSet {
-    pub fn new() -> ServerRequestHandlerSet {
-        ServerRequestHandlerSet {
-            handlers: Arc::new(Mutex::new(Vec::new())),
+
+/// the next server of `server_list` in rotation, malformed addresses are skipped.
+fn next_server_info(server_list: &dyn ServerListFactory) -> Option<ServerInfo> {
+    for _ in 0..max(server_list.server_list().len(), 1) {
+        let server = server_list.next_server()?;
+        match try_resolve_server_info(&server) {
+            Ok(server_info) => return Some(server_info),
+            Err(error) => log::warn!("skip server, {}", error),
         }
     }
+    None
+}
 
-    pub fn register(&mut self, handler: ServerRequestHandler) {
-        let mut lock = self.handlers.lock().unwrap();
-        lock.push(handler);
+fn now_millis() -> u64 {
+    Local::now().timestamp_millis() as u64
+}
+
+/// A function to create a new channel with specified [ServerInfo]
+async fn create_new_channel(server_info: &ServerInfo) -> NacosResult<Channel> {
+    const SCHEMA_HTTPS: &'static str = "https";
+    const SCHEMA_HTTP: &'static str = "http";
+    let ip = server_info.server_ip.as_str();
+    let port = server_info.server_port + rpc_port_offset();
+    let schema = if server_info.enable_ssl {
+        SCHEMA_HTTPS
+    } else {
+        SCHEMA_HTTP
+    };
+    let url = format!(
+        "{schema}://{ip}:{port}",
+        schema = schema,
+        ip = ip,
+        port = port
+    );
+    let uri = Uri::try_from(url.as_str()).map_err(NacosError::client)?;
+    let mut endpoint = Channel::builder(uri);
+    let channel = endpoint
+        .keep_alive_timeout(Duration::from_millis(keep_alive_time_mills()))
+        .timeout(Duration::from_millis(timeout_mills()))
+        .connect_timeout(Duration::from_millis(connect_timeout_mills()))
+        .concurrency_limit(400)
+        .tcp_nodelay(true)
+        .connect()
+        .await?;
+    Ok(channel)
+}
+
+pub fn keep_alive_time_mills() -> u64 {
+    const DEFAULT_KEEP_ALIVE_TIME_MILLS: u64 = 6 * 60 * 1000;
+    let keep_alive_time = get_env(
+        "nacos.remote.grpc.keep.alive.millis",
+        DEFAULT_KEEP_ALIVE_TIME_MILLS.to_string().as_str(),
+    );
+    let keep_alive_time = keep_alive_time.parse::<u64>();
+    match keep_alive_time {
+        Ok(mills) => mills,
+        Err(error) => {
+            log::warn!(
+                "property `nacos.remote.grpc.keep.alive.millis` is not a valid integer, {}",
+                error
+            );
+            DEFAULT_KEEP_ALIVE_TIME_MILLS
+        }
     }
 }
-pub struct RpcClient {
-    name: String,
-    pub last_active_timestamp: AtomicI64,
-    server_request_handlers: ServerRequestHandlerSet,
+
+pub fn timeout_mills() -> u64 {
+    const DEFAULT_TIMEOUT_MILLS: u64 = 5000;
+    let timeout = get_env(
+        "nacos.remote.grpc.timeout.millis",
+        DEFAULT_TIMEOUT_MILLS.to_string().as_str(),
+    );
+    let timeout = timeout.parse::<u64>();
+    match timeout {
+        Ok(mills) => mills,
+        Err(error) => {
+            log::warn!(
+                "property `nacos.remote.grpc.timeout.millis` is not a valid integer, {}",
+                error
+            );
+            DEFAULT_TIMEOUT_MILLS
+        }
+    }
 }
 
-impl RpcClient {
-    pub async fn handle_server_request(&mut self, request: String) -> Option<String> {
-        {
-            *self.last_active_timestamp.get_mut() = Local::now().timestamp();
+pub fn connect_timeout_mills() -> u64 {
+    const DEFAULT_CONNECT_TIMEOUT_MILLS: u64 = 5000;
+    let connect_timeout = get_env(
+        "nacos.remote.grpc.connect.timeout.millis",
+        DEFAULT_CONNECT_TIMEOUT_MILLS.to_string().as_str(),
+    );
+    let connect_timeout = connect_timeout.parse::<u64>();
+    match connect_timeout {
+        Ok(mills) => mills,
+        Err(error) => {
+            log::warn!(
+                "property `nacos.remote.grpc.connect.timeout.millis` is not a valid integer, {}",
+                error
+            );
+            DEFAULT_CONNECT_TIMEOUT_MILLS
         }
-        {
-            let handlers = self.server_request_handlers.handlers.lock().unwrap();
-            let request = request.to_string();
-            for handle in handlers.iter() {
-                let req = request.clone();
-                let response = handle(req);
-                if response.is_some() {
-                    return Some(response.unwrap());
-                }
-            }
-            return None;
+    }
+}
+
+pub fn concurrency_limit() -> usize {
+    const DEFAULT_CONCURRENCY_LIMIT: usize = 1024;
+    let limit = get_env(
+        "nacos.remote.grpc.concurrency.limit",
+        DEFAULT_CONCURRENCY_LIMIT.to_string().as_str(),
+    );
+    let limit = limit.parse::<usize>();
+    match limit {
+        Ok(limit) => limit,
+        Err(error) => {
+            log::warn!(
+                "property `nacos.remote.grpc.concurrency.limit` is not a valid integer, {}",
+                error
+            );
+            DEFAULT_CONCURRENCY_LIMIT
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        next_server_info, resolve_server_info, try_resolve_server_info, ClientState,
+        RequestHeaderProvider, RpcClient, ServerListFactory,
+    };
+    use crate::common::remote::client::grpc::utils::convert_request;
+    use nacos_api::api::remote::request::HealthCheckRequest;
+    use nacos_core::error::NacosError;
+    use nacos_proto::grpc::Payload;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tonic::transport::Endpoint;
+
+    #[test]
+    fn test_client_state() {
+        let client = RpcClient::new(None, Default::default());
+        assert_eq!(client.state(), ClientState::Starting);
+        assert!(client.current_connection().is_err());
+        client.shutdown();
+        assert!(client.is_shutdown());
+        // a shutdown client never reconnects.
+        client.switch_server_async(None, false);
+        assert_eq!(client.state(), ClientState::Shutdown);
+    }
+
+    #[test]
+    fn test_resolve_server_info() {
+        let server_info = resolve_server_info("https://127.0.0.1:8849".to_string());
+        assert_eq!(server_info.address(), "127.0.0.1:8849");
+        assert!(server_info.enable_ssl);
+        let server_info = resolve_server_info("127.0.0.1".to_string());
+        assert_eq!(server_info.server_port, 8848);
+        assert!(!server_info.enable_ssl);
+        let server_info = try_resolve_server_info("nacos-http.svc:8848").unwrap();
+        assert_eq!(server_info.address(), "nacos-http.svc:8848");
+        assert!(try_resolve_server_info("10.0.0.1:abc").is_err());
+    }
+
+    struct StaticServerList(Vec<&'static str>, AtomicUsize);
+
+    impl ServerListFactory for StaticServerList {
+        fn next_server(&self) -> Option<String> {
+            let index = self.1.fetch_add(1, Ordering::SeqCst);
+            Some(self.0[index % self.0.len()].to_string())
+        }
+
+        fn server_list(&self) -> Vec<String> {
+            self.0.iter().map(|server| server.to_string()).collect()
         }
     }
+
+    #[test]
+    fn test_skip_malformed_server() {
+        let server_list =
+            StaticServerList(vec!["10.0.0.1:abc", "10.0.0.2:8848"], Default::default());
+        let server_info = next_server_info(&server_list).unwrap();
+        assert_eq!(server_info.address(), "10.0.0.2:8848");
+        let server_list = StaticServerList(vec!["10.0.0.1:abc"], Default::default());
+        assert!(next_server_info(&server_list).is_none());
+    }
+
+    struct TokenProvider;
+
+    impl RequestHeaderProvider for TokenProvider {
+        fn headers(&self, payload: &Payload) -> HashMap<String, String> {
+            let ty = payload.metadata.as_ref().unwrap().r#type.clone();
+            vec![("accessToken".to_string(), format!("token-of-{}", ty))]
+                .into_iter()
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_attach_headers() {
+        let client = RpcClient::new(None, Default::default());
+        client.register_header_provider(TokenProvider);
+        let mut payload = convert_request(&HealthCheckRequest::new());
+        client.attach_headers(&mut payload);
+        let headers = &payload.metadata.as_ref().unwrap().headers;
+        assert_eq!(headers["accessToken"], "token-of-HealthCheckRequest");
+    }
+
+    #[test]
+    fn test_bind_unreachable_request_stream() {
+        let client = RpcClient::new(None, Default::default());
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let result = runtime.block_on(async {
+            // nothing listens on port 1.
+            let channel = Endpoint::from_static("http://127.0.0.1:1").connect_lazy();
+            client
+                .bind_request_stream(&channel, Default::default(), Default::default())
+                .await
+        });
+        assert!(matches!(result, Err(NacosError::Disconnected(_))));
+    }
 }
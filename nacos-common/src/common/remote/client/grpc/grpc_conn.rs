@@ -1,94 +1,422 @@
-use crate::common::remote::client::conn::Connection;
+//! GrpcConnection
+use crate::common::remote::client::conn::{Connection, ConnectionMeta, ServerInfo};
+use crate::common::remote::client::grpc::registry::{PayloadKind, PayloadRegistry};
 use crate::common::remote::client::grpc::utils::{
     convert_request, convert_response, parse_response,
 };
+use nacos_api::api::remote::request::RpcRequest;
 use nacos_api::api::traits::{Callback, RequestExt, Requester, ResponseExt};
 use nacos_core::error::{NacosError, NacosResult};
 use nacos_proto::grpc::request_client::RequestClient;
 use nacos_proto::grpc::Payload;
+use nacos_proto::log_response;
+use std::collections::HashMap;
+use std::ops::DerefMut;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use tonic::transport::Channel;
 use tonic::Request;
 
+static REQUEST_ID: AtomicU64 = AtomicU64::new(0);
+
+/// generate a unique id of requests sent by this client.
+pub(crate) fn next_request_id() -> String {
+    REQUEST_ID.fetch_add(1, Ordering::SeqCst).to_string()
+}
+
+/// Requests sent over the bi-stream waiting for responses, keyed by requestId.
+#[derive(Clone, Default)]
+pub(crate) struct PendingRequests(Arc<Mutex<HashMap<String, oneshot::Sender<Payload>>>>);
+
+impl PendingRequests {
+    fn insert(&self, request_id: String) -> oneshot::Receiver<Payload> {
+        let (sender, receiver) = oneshot::channel();
+        self.0.lock().unwrap().insert(request_id, sender);
+        receiver
+    }
+
+    fn remove(&self, request_id: &str) {
+        self.0.lock().unwrap().remove(request_id);
+    }
+
+    /// complete the pending request if `payload` is its response,
+    /// otherwise the payload is returned to be handled as a server request.
+    pub(crate) fn complete(&self, payload: Payload) -> Option<Payload> {
+        let is_response = payload
+            .metadata
+            .as_ref()
+            .and_then(|metadata| PayloadRegistry::global().kind(&metadata.r#type))
+            .map(|kind| kind == PayloadKind::Response)
+            .unwrap_or(false);
+        if !is_response {
+            return Some(payload);
+        }
+        let request_id = payload
+            .body
+            .as_ref()
+            .and_then(|body| serde_json::from_slice::<serde_json::Value>(&body.value).ok())
+            .and_then(|body| body["requestId"].as_str().map(ToString::to_string));
+        let sender = match request_id {
+            Some(request_id) => self.0.lock().unwrap().remove(&request_id),
+            None => None,
+        };
+        match sender {
+            Some(sender) => {
+                let _ = sender.send(payload);
+                None
+            }
+            None => Some(payload),
+        }
+    }
+
+    /// fail all pending requests, e.g. the stream is closed.
+    pub(crate) fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+}
+
 pub struct GrpcConnection {
-    pub conn: Connection,
-    pub channel: Channel,
-    payload_stream_observer: mpsc::Sender<Payload>,
-    grpc_future_service_stub: RequestClient<Channel>,
+    pub(crate) connection: Mutex<Connection>,
+    pub(crate) connection_id: Option<String>,
+    // shared with the stream reader, an abandoned connection does not trigger reconnect.
+    pub(crate) abandon: Arc<AtomicBool>,
+    pub(crate) server_info: ServerInfo,
+    pub(crate) channel: Option<Channel>,
+    // to observe channel stream.
+    pub(crate) sender: Option<mpsc::Sender<Payload>>,
+    // client to handle Request/config
+    pub(crate) request_stub: Option<RequestClient<Channel>>,
+    // shared with the stream reader to complete requests sent over the bi-stream.
+    pub(crate) pending_requests: PendingRequests,
+}
+
+impl GrpcConnection {
+    /// create a [GrpcConnection] instance.
+    pub fn new(server_info: ServerInfo) -> Self {
+        GrpcConnection {
+            connection: Mutex::new(Connection::default()),
+            connection_id: None,
+            abandon: Default::default(),
+            server_info,
+            channel: None,
+            sender: None,
+            request_stub: None,
+            pending_requests: Default::default(),
+        }
+    }
+
+    pub fn new_with_meta(
+        server_info: ServerInfo,
+        meta_info: ConnectionMeta,
+        channel: Channel,
+        sender: mpsc::Sender<Payload>,
+    ) -> Self {
+        let mut connection = Connection::default();
+        connection.meta_info = Some(meta_info);
+        GrpcConnection {
+            connection: Mutex::new(connection),
+            connection_id: None,
+            abandon: Default::default(),
+            server_info,
+            channel: Some(channel),
+            sender: Some(sender),
+            request_stub: None,
+            pending_requests: Default::default(),
+        }
+    }
+
+    /// update last active time of the connection to now.
+    pub fn fresh_active_time(&self) {
+        self.connection.lock().unwrap().fresh_active_time();
+    }
+
+    /// timestamp in seconds when the server was active lastly, `None` if no meta info is attached.
+    pub fn last_active_time(&self) -> Option<u64> {
+        let connection = self.connection.lock().unwrap();
+        connection
+            .meta_info
+            .as_ref()
+            .map(|meta| meta.last_active_time)
+    }
+
+    pub fn connection_id(&self) -> Option<&str> {
+        self.connection_id.as_deref()
+    }
+
+    /// meta info of the connection in json.
+    pub fn meta_info(&self) -> Option<String> {
+        let connection = self.connection.lock().unwrap();
+        connection.meta_info.as_ref().map(ToString::to_string)
+    }
+
+    /// mark the connection abandoned, e.g. it is replaced or the client shuts down.
+    pub fn abandon(&self) {
+        self.abandon.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_abandon(&self) -> bool {
+        self.abandon.load(Ordering::SeqCst)
+    }
+
+    pub async fn request_timeout<Req>(
+        &self,
+        request: Req,
+        timeout_millis: u64,
+    ) -> NacosResult<Payload>
+    where
+        Req: RequestExt,
+    {
+        self.request_payload(convert_request::<Req>(&request), timeout_millis)
+            .await
+    }
+
+    /// send a payload converted from request, e.g. to resend it on failure.
+    pub async fn request_payload(
+        &self,
+        payload: Payload,
+        timeout_millis: u64,
+    ) -> NacosResult<Payload> {
+        let mut request = Request::new(payload);
+        request.set_timeout(Duration::from_millis(timeout_millis));
+        let mut request_stub = self.request_stub.clone().ok_or(NacosError::Disconnected(
+            "connection is not ready".to_string(),
+        ))?;
+        let resp = request_stub.request(request).await?;
+        log_response(&resp);
+        Ok(resp.into_inner())
+    }
+
+    /// send `request` over the bi-stream and wait for the response with the same requestId.
+    pub async fn request_bi_stream<Req>(
+        &self,
+        mut request: Req,
+        timeout_millis: u64,
+    ) -> NacosResult<Payload>
+    where
+        Req: RequestExt + DerefMut<Target = RpcRequest>,
+    {
+        let sender = self.sender.as_ref().ok_or(NacosError::Disconnected(
+            "connection is not ready".to_string(),
+        ))?;
+        let request_id = next_request_id();
+        request.request_id = Some(request_id.clone());
+        let receiver = self.pending_requests.insert(request_id.clone());
+        let payload = convert_request::<Req>(&request);
+        if sender.send(payload).await.is_err() {
+            self.pending_requests.remove(&request_id);
+            return Err(NacosError::Disconnected("bi stream is closed".to_string()));
+        }
+        match tokio::time::timeout(Duration::from_millis(timeout_millis), receiver).await {
+            Ok(Ok(payload)) => Ok(payload),
+            Ok(Err(_)) => Err(NacosError::Disconnected(format!(
+                "connection is closed before response of request {}",
+                request_id
+            ))),
+            Err(_) => {
+                self.pending_requests.remove(&request_id);
+                Err(NacosError::Timeout(format!(
+                    "request {} timeout after {}ms",
+                    request_id, timeout_millis
+                )))
+            }
+        }
+    }
+
+    pub async fn send_request<Req>(&self, mut request: Req) -> NacosResult<()>
+    where
+        Req: RequestExt + DerefMut<Target = RpcRequest>,
+    {
+        let sender = self.sender.as_ref().unwrap();
+        if request.request_id.is_none() {
+            request.request_id = Some(next_request_id());
+        }
+        let payload = convert_request::<Req>(&request);
+        return if sender.send(payload).await.is_ok() {
+            Ok(())
+        } else {
+            log::error!("send config error.");
+            Err(NacosError::Disconnected("config send failed".to_string()))
+        };
+    }
+
+    pub async fn send_response<Resp>(&self, response: Resp) -> NacosResult<()>
+    where
+        Resp: ResponseExt,
+    {
+        let sender = self.sender.as_ref().unwrap();
+        let payload = convert_response::<Resp>(&response);
+        return if sender.send(payload).await.is_ok() {
+            Ok(())
+        } else {
+            log::error!("send response error.");
+            Err(NacosError::Disconnected("response send failed".to_string()))
+        };
+    }
 }
 
 #[async_trait::async_trait]
 impl Requester for GrpcConnection {
-    async fn request<Req, Resp>(
-        &mut self,
-        request: Req,
-        timeout_mills: u64,
-    ) -> nacos_core::error::NacosResult<Resp>
+    async fn request<Req, Resp>(&mut self, request: Req, timeout_mills: u64) -> NacosResult<Resp>
     where
         Req: RequestExt + Send + Sync + 'static,
         Resp: ResponseExt + Send + Sync + 'static,
     {
-        let payload = convert_request(&request);
-        let mut request = Request::new(payload);
+        let mut request_stub = self.request_stub.clone().ok_or(NacosError::Disconnected(
+            "connection is not ready".to_string(),
+        ))?;
+        let mut request = Request::new(convert_request(&request));
         request.set_timeout(Duration::from_millis(timeout_mills));
-        let response = self.grpc_future_service_stub.request(request).await?;
-        let response = response.into_inner();
-        let response: Resp = parse_response::<Resp>(&response)?;
-        Ok(response)
+        let response = request_stub.request(request).await?;
+        log_response(&response);
+        parse_response::<Resp>(&response.into_inner())
     }
 
     async fn async_request<Req, Resp>(
         &mut self,
         request: Req,
         callback: Callback,
-    ) -> nacos_core::error::NacosResult<()>
+    ) -> NacosResult<()>
     where
         Req: RequestExt + Send + Sync + 'static,
         Resp: ResponseExt + Send + Sync + 'static,
     {
-        let payload = convert_request(&request);
-        let mut request = Request::new(payload);
-        let mut client = self.grpc_future_service_stub.clone();
-        let timeout = callback.get_timeout();
-        request.set_timeout(Duration::from_millis(timeout));
+        let mut request_stub = self.request_stub.clone().ok_or(NacosError::Disconnected(
+            "connection is not ready".to_string(),
+        ))?;
+        let timeout = Duration::from_millis(callback.get_timeout());
+        let mut request = Request::new(convert_request(&request));
+        request.set_timeout(timeout);
         tokio::spawn(async move {
-            let response = client.request(request).await;
-            match response {
-                Ok(resp) => callback.on_response(resp.into_inner().body.unwrap().value),
-                Err(error) => {
-                    callback.on_exception(NacosError::new(error));
-                }
+            match tokio::time::timeout(timeout, request_stub.request(request)).await {
+                Ok(Ok(response)) => match parse_response::<Resp>(&response.into_inner()) {
+                    Ok(response) => callback.on_response(response.to_vec()),
+                    Err(error) => callback.on_exception(error),
+                },
+                Ok(Err(status)) => callback.on_exception(NacosError::from(status)),
+                Err(_) => callback.on_exception(NacosError::Timeout(format!(
+                    "request timeout after {}ms",
+                    timeout.as_millis()
+                ))),
             }
         });
         Ok(())
     }
 }
 
-impl GrpcConnection {
-    pub fn payload_stream_observer(&self) -> mpsc::Sender<Payload> {
-        self.payload_stream_observer.clone()
+#[cfg(test)]
+mod tests {
+    use super::GrpcConnection;
+    use crate::common::remote::client::conn::ServerInfo;
+    use crate::common::remote::client::grpc::utils::{
+        convert_response, parse_request, parse_response,
+    };
+    use nacos_api::api::remote::request::HealthCheckRequest;
+    use nacos_api::api::remote::response::HealthCheckResponse;
+    use nacos_api::api::traits::{RequestCallback, Requester};
+    use nacos_core::error::NacosError;
+    use nacos_proto::grpc::request_client::RequestClient;
+    use std::convert::TryFrom;
+    use tokio::sync::mpsc;
+    use tonic::transport::Endpoint;
+
+    fn connection() -> (GrpcConnection, mpsc::Receiver<nacos_proto::grpc::Payload>) {
+        let mut connection = GrpcConnection::new(ServerInfo::new("127.0.0.1".to_string(), 8848));
+        let (sender, receiver) = mpsc::channel(16);
+        connection.sender = Some(sender);
+        (connection, receiver)
     }
 
-    pub fn grpc_future_service_stub(&self) -> RequestClient<Channel> {
-        self.grpc_future_service_stub.clone()
+    #[test]
+    fn test_request_bi_stream() {
+        let (connection, mut outbound) = connection();
+        let pending = connection.pending_requests.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let payload = runtime.block_on(async move {
+            // a stub server replying to the request.
+            tokio::spawn(async move {
+                let payload = outbound.recv().await.unwrap();
+                let request = parse_request::<HealthCheckRequest>(&payload).unwrap();
+                let mut response = HealthCheckResponse {
+                    inner: Default::default(),
+                };
+                response.request_id = request.request_id.clone();
+                assert!(pending.complete(convert_response(&response)).is_none());
+            });
+            connection
+                .request_bi_stream(HealthCheckRequest::new(), 3000)
+                .await
+                .unwrap()
+        });
+        let response = parse_response::<HealthCheckResponse>(&payload).unwrap();
+        assert!(response.request_id.is_some());
     }
 
-    pub async fn send_request<Req>(&self, request: Req) -> NacosResult<()>
-    where
-        Req: RequestExt,
-    {
-        let payload = convert_request(&request);
-        let _ = self.payload_stream_observer().send(payload).await?;
-        Ok(())
+    #[test]
+    fn test_request_bi_stream_timeout() {
+        let (connection, _outbound) = connection();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let result = runtime.block_on(connection.request_bi_stream(HealthCheckRequest::new(), 50));
+        assert!(result.is_err());
+        assert!(connection.pending_requests.0.lock().unwrap().is_empty());
     }
 
-    pub async fn send_response<Resp>(&self, response: Resp) -> NacosResult<()>
-    where
-        Resp: ResponseExt,
-    {
+    #[test]
+    fn test_uncorrelated_response_passes_through() {
+        let (connection, _outbound) = connection();
+        let mut response = HealthCheckResponse {
+            inner: Default::default(),
+        };
+        response.request_id = Some("unknown".to_string());
         let payload = convert_response(&response);
-        let _ = self.payload_stream_observer().send(payload).await?;
-        Ok(())
+        assert!(connection.pending_requests.complete(payload).is_some());
+    }
+
+    struct ChannelCallback(std::sync::Mutex<std::sync::mpsc::Sender<Result<Vec<u8>, String>>>);
+
+    impl RequestCallback for ChannelCallback {
+        fn get_timeout(&self) -> u64 {
+            100
+        }
+
+        fn on_response(&self, response: Vec<u8>) {
+            self.0.lock().unwrap().send(Ok(response)).unwrap();
+        }
+
+        fn on_exception(&self, error: NacosError) {
+            self.0.lock().unwrap().send(Err(error.to_string())).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_async_request_timeout() {
+        // a server accepting connections but never answering.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let _streams: Vec<_> = listener.incoming().collect();
+        });
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        runtime.block_on(async {
+            let (mut connection, _outbound) = connection();
+            let channel = Endpoint::try_from(format!("http://127.0.0.1:{}", port))
+                .unwrap()
+                .connect_lazy();
+            connection.request_stub = Some(RequestClient::new(channel));
+            let callback = Box::new(ChannelCallback(std::sync::Mutex::new(sender)));
+            connection
+                .async_request::<HealthCheckRequest, HealthCheckResponse>(
+                    HealthCheckRequest::new(),
+                    callback,
+                )
+                .await
+                .unwrap();
+        });
+        let result = receiver
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .unwrap();
+        assert!(result.is_err());
     }
 }
@@ -1,2 +1,3 @@
 pub mod grpc_conn;
+pub mod registry;
 pub mod utils;
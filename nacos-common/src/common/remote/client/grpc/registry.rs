@@ -0,0 +1,164 @@
+//! A registry mapping the type names of payloads on the wire to their decoders.
+use nacos_api::api::remote::request::*;
+use nacos_api::api::remote::response::*;
+use nacos_api::api::traits::{RequestExt, ResponseExt};
+use nacos_core::error::{NacosError, NacosResult};
+use serde::de::DeserializeOwned;
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+type Decoder = fn(&[u8]) -> NacosResult<Box<dyn Any + Send>>;
+
+lazy_static::lazy_static! {
+    static ref GLOBAL_REGISTRY: PayloadRegistry = PayloadRegistry::with_builtin_types();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadKind {
+    Request,
+    Response,
+}
+
+#[derive(Clone, Copy)]
+struct PayloadEntry {
+    kind: PayloadKind,
+    decoder: Decoder,
+}
+
+/// Registry of payload types keyed by [RequestExt::TYPE_NAME] and [ResponseExt::TYPE_NAME].
+pub struct PayloadRegistry {
+    entries: RwLock<HashMap<&'static str, PayloadEntry>>,
+}
+
+fn decode<T>(body: &[u8]) -> NacosResult<Box<dyn Any + Send>>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    Ok(Box::new(serde_json::from_slice::<T>(body)?))
+}
+
+impl PayloadRegistry {
+    /// create an empty registry.
+    pub fn new() -> Self {
+        PayloadRegistry {
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// the registry shared by all connections, request and response types of nacos-api
+    /// are registered already.
+    pub fn global() -> &'static PayloadRegistry {
+        &GLOBAL_REGISTRY
+    }
+
+    fn with_builtin_types() -> Self {
+        let registry = PayloadRegistry::new();
+        registry.register_request::<ClientDetectionRequest>();
+        registry.register_request::<ConnectResetRequest>();
+        registry.register_request::<ServerCheckRequest>();
+        registry.register_request::<HealthCheckRequest>();
+        registry.register_request::<ConnectionSetupRequest>();
+        registry.register_request::<ConfigChangeNotifyRequest>();
+        registry.register_request::<ConfigBatchListenRequest>();
+        registry.register_request::<ConfigQueryRequest>();
+        registry.register_request::<ConfigPublishRequest>();
+        registry.register_request::<ConfigRemoveRequest>();
+        registry.register_response::<ClientDetectionResponse>();
+        registry.register_response::<ConnectResetResponse>();
+        registry.register_response::<ErrorResponse>();
+        registry.register_response::<HealthCheckResponse>();
+        registry.register_response::<ServerCheckResponse>();
+        registry.register_response::<ServerLoaderInfoResponse>();
+        registry.register_response::<ServerReloadResponse>();
+        registry.register_response::<Response>();
+        registry.register_response::<ConfigQueryResponse>();
+        registry.register_response::<ConfigPublishResponse>();
+        registry.register_response::<ConfigRemoveResponse>();
+        registry.register_response::<ConfigChangeBatchListenResponse>();
+        registry.register_response::<ConfigChangeNotifyResponse>();
+        registry
+    }
+
+    pub fn register_request<Req>(&self)
+    where
+        Req: RequestExt + Send + 'static,
+    {
+        self.register(Req::TYPE_NAME, PayloadKind::Request, decode::<Req>);
+    }
+
+    pub fn register_response<Resp>(&self)
+    where
+        Resp: ResponseExt + Send + 'static,
+    {
+        self.register(Resp::TYPE_NAME, PayloadKind::Response, decode::<Resp>);
+    }
+
+    fn register(&self, ty: &'static str, kind: PayloadKind, decoder: Decoder) {
+        let mut lock = self.entries.write().unwrap();
+        lock.insert(ty, PayloadEntry { kind, decoder });
+    }
+
+    /// the kind of payload type `ty`, `None` if it is not registered.
+    pub fn kind(&self, ty: &str) -> Option<PayloadKind> {
+        let lock = self.entries.read().unwrap();
+        lock.get(ty).map(|entry| entry.kind)
+    }
+
+    pub fn contains(&self, ty: &str) -> bool {
+        self.kind(ty).is_some()
+    }
+
+    /// decode the body of payload type `ty` with its registered decoder.
+    pub fn decode(&self, ty: &str, body: &[u8]) -> NacosResult<Box<dyn Any + Send>> {
+        let entry = self.entries.read().unwrap().get(ty).copied();
+        match entry {
+            Some(entry) => (entry.decoder)(body),
+            None => Err(NacosError::Serialization(format!(
+                "unknown payload type `{}`",
+                ty
+            ))),
+        }
+    }
+}
+
+impl Default for PayloadRegistry {
+    fn default() -> Self {
+        PayloadRegistry::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PayloadKind, PayloadRegistry};
+    use crate::common::remote::client::grpc::utils::{convert_response, parse_response};
+    use nacos_api::api::remote::response::{ConfigQueryResponse, ErrorResponse};
+    use nacos_core::error::NacosError;
+
+    #[test]
+    fn test_decode_registered_type() {
+        let registry = PayloadRegistry::global();
+        assert_eq!(
+            registry.kind("ConfigQueryRequest"),
+            Some(PayloadKind::Request)
+        );
+        assert_eq!(registry.kind("ErrorResponse"), Some(PayloadKind::Response));
+        let body = br#"{"resultCode":200,"errorCode":0,"content":"k=v","md5":"md5"}"#;
+        let decoded = registry.decode("ConfigQueryResponse", body).unwrap();
+        let response = decoded.downcast::<ConfigQueryResponse>().unwrap();
+        assert_eq!(response.content.as_deref(), Some("k=v"));
+        let error = registry.decode("UnknownResponse", body).unwrap_err();
+        assert!(error.to_string().contains("unknown payload type"));
+    }
+
+    #[test]
+    fn test_error_response_to_server_error() {
+        let payload = convert_response(&ErrorResponse::build(301, "no right".to_string()));
+        let error = parse_response::<ConfigQueryResponse>(&payload)
+            .err()
+            .unwrap();
+        assert!(matches!(error, NacosError::Server { code: 301, .. }));
+        assert_eq!(error.code(), 301);
+        assert!(error.to_string().contains("no right"));
+    }
+}
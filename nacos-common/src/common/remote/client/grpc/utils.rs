@@ -1,21 +1,26 @@
+use crate::common::remote::client::grpc::registry::PayloadRegistry;
 use local_ip_address::local_ip;
+use nacos_api::api::remote::request::RpcRequest;
+use nacos_api::api::remote::response::ErrorResponse;
 use nacos_api::api::traits::{RequestExt, ResponseExt};
 use nacos_core::error::{NacosError, NacosResult};
 use nacos_proto::grpc::{Metadata, Payload};
 use prost_types::Any;
-use std::any::type_name;
+use serde::Serialize;
 use std::net::{IpAddr, Ipv4Addr};
+use std::ops::DerefMut;
 
-fn convert(metadata: Metadata, body: Vec<u8>) -> Payload {
+fn convert<T: Serialize>(metadata: Metadata, value: &T) -> Payload {
     Payload {
         metadata: Some(metadata),
         body: Some(Any {
             type_url: "".to_string(),
-            value: body,
+            value: serde_json::to_vec(value).unwrap(),
         }),
     }
 }
-fn local_ip_address() -> String {
+
+pub fn local_ip_address() -> String {
     local_ip()
         .unwrap_or(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)))
         .to_string()
@@ -27,11 +32,11 @@ where
     Resp: ResponseExt,
 {
     let metadata = Metadata {
-        r#type: response.ty_name(),
+        r#type: Resp::TYPE_NAME.to_string(),
         client_ip: local_ip_address(),
         headers: Default::default(),
     };
-    convert(metadata, response.to_vec())
+    convert(metadata, response)
 }
 
 /// convert request to payload
@@ -40,37 +45,69 @@ where
     Req: RequestExt,
 {
     let metadata = Metadata {
-        r#type: request.ty_name(),
+        r#type: Req::TYPE_NAME.to_string(),
         client_ip: local_ip_address(),
         headers: request.headers(),
     };
-    convert(metadata, request.to_vec())
+    convert(metadata, request)
 }
 
+/// parse request from payload, headers are taken from the metadata.
+pub fn parse_request<Req>(payload: &Payload) -> NacosResult<Req>
+where
+    Req: RequestExt + DerefMut<Target = RpcRequest> + 'static,
+{
+    let mut request = decode_payload::<Req>(payload, Req::TYPE_NAME)?;
+    if let Some(ref metadata) = payload.metadata {
+        request.headers = metadata.headers.clone();
+    }
+    Ok(request)
+}
+
+/// parse response from payload, an `ErrorResponse` from server is returned as [ServerError].
 pub fn parse_response<Resp>(payload: &Payload) -> NacosResult<Resp>
 where
-    Resp: ResponseExt,
+    Resp: ResponseExt + 'static,
 {
-    let _ = check_type::<Resp>(payload)?;
-    let bytes = payload.body.as_ref().unwrap().value.as_slice();
-    Ok(serde_json::from_slice::<Resp>(bytes).unwrap())
+    if payload_type(payload)? == ErrorResponse::TYPE_NAME
+        && Resp::TYPE_NAME != ErrorResponse::TYPE_NAME
+    {
+        let response = decode_payload::<ErrorResponse>(payload, ErrorResponse::TYPE_NAME)?;
+        return Err(NacosError::server(
+            response.error_code as i32,
+            response.message.clone().unwrap_or_default(),
+        ));
+    }
+    decode_payload::<Resp>(payload, Resp::TYPE_NAME)
 }
 
-fn check_type<Ty>(payload: &Payload) -> NacosResult<()> {
-    let ty_name = payload.metadata.as_ref().unwrap().r#type.as_str();
-    let ty = type_name::<Ty>();
-    let ty = if ty.contains("::") {
-        ty.rsplit_once("::").unwrap().1
-    } else {
-        ty
-    };
-    return if ty != ty_name {
-        log::error!("error for expected type: {}, actual is : {}", ty, ty_name);
-        Err(NacosError::msg(format!(
-            "expected  `{}`, found `{}`",
-            ty, ty_name
-        )))
-    } else {
-        Ok(())
-    };
+/// the type name of payload in metadata.
+pub fn payload_type(payload: &Payload) -> NacosResult<&str> {
+    let metadata = payload
+        .metadata
+        .as_ref()
+        .ok_or(NacosError::Serialization("metadata is empty".to_string()))?;
+    Ok(metadata.r#type.as_str())
+}
+
+fn decode_payload<T: 'static>(payload: &Payload, expected: &str) -> NacosResult<T> {
+    let ty = payload_type(payload)?;
+    if ty != expected {
+        log::error!("error for expected type: {}, actual is : {}", expected, ty);
+        return Err(NacosError::Serialization(format!(
+            "expected `{}`, found `{}`",
+            expected, ty
+        )));
+    }
+    let body = payload.body.as_ref().ok_or(NacosError::Serialization(
+        "payload body is empty".to_string(),
+    ))?;
+    let decoded = PayloadRegistry::global().decode(ty, body.value.as_slice())?;
+    match decoded.downcast::<T>() {
+        Ok(value) => Ok(*value),
+        Err(_) => Err(NacosError::Serialization(format!(
+            "payload type `{}` is registered with another decoder",
+            ty
+        ))),
+    }
 }
@@ -0,0 +1,217 @@
+//! A module to process requests pushed by server.
+use crate::common::remote::client::cli::RpcClient;
+use crate::common::remote::client::conn::ServerInfo;
+use crate::common::remote::client::grpc::registry::PayloadRegistry;
+use crate::common::remote::client::grpc::utils::{convert_response, parse_request};
+use nacos_api::api::remote::request::{
+    ClientDetectionRequest, ConnectResetRequest, RpcRequest, ServerRequest,
+};
+use nacos_api::api::remote::response::{
+    ClientDetectionResponse, ConnectResetResponse, RpcResponse,
+};
+use nacos_api::api::traits::{RequestExt, ResponseExt};
+use nacos_core::error::NacosResult;
+use nacos_proto::grpc::Payload;
+use std::collections::HashMap;
+use std::ops::DerefMut;
+use std::sync::{Arc, RwLock, Weak};
+
+#[async_trait::async_trait]
+pub trait ServerRequestHandler: Send + Sync + 'static {
+    /// Request type to process, the handler is registered by its type name.
+    type Request: ServerRequest + RequestExt + DerefMut<Target = RpcRequest> + Send + 'static;
+    /// Response type to reply to server.
+    type Response: ResponseExt + DerefMut<Target = RpcResponse> + Send + 'static;
+    /// A function to process the request from server side, `None` means no reply.
+    async fn request_reply(&self, request: Self::Request) -> NacosResult<Option<Self::Response>>;
+}
+
+/// A type-erased [ServerRequestHandler] working on payloads.
+#[async_trait::async_trait]
+trait PayloadHandler: Send + Sync {
+    async fn handle(&self, payload: &Payload) -> NacosResult<Option<Payload>>;
+}
+
+struct TypedHandler<H>(H);
+
+#[async_trait::async_trait]
+impl<H> PayloadHandler for TypedHandler<H>
+where
+    H: ServerRequestHandler,
+{
+    async fn handle(&self, payload: &Payload) -> NacosResult<Option<Payload>> {
+        let request = parse_request::<H::Request>(payload)?;
+        let request_id = request.request_id.clone();
+        let response = self.0.request_reply(request).await?;
+        Ok(response.map(|mut response| {
+            response.request_id = request_id;
+            convert_response(&response)
+        }))
+    }
+}
+
+/// Registry of [ServerRequestHandler] keyed by the payload type.
+#[derive(Clone, Default)]
+pub struct ServerRequestHandlers {
+    handlers: Arc<RwLock<HashMap<&'static str, Arc<dyn PayloadHandler>>>>,
+}
+
+impl ServerRequestHandlers {
+    /// register a handler, it replaces the handler registered for the same request type.
+    /// The request and response types are added to [PayloadRegistry::global] to be decoded.
+    pub fn register<H>(&self, handler: H)
+    where
+        H: ServerRequestHandler,
+    {
+        let registry = PayloadRegistry::global();
+        registry.register_request::<H::Request>();
+        registry.register_response::<H::Response>();
+        let mut lock = self.handlers.write().unwrap();
+        lock.insert(H::Request::TYPE_NAME, Arc::new(TypedHandler(handler)));
+    }
+
+    pub fn contains(&self, ty: &str) -> bool {
+        self.handlers.read().unwrap().contains_key(ty)
+    }
+
+    /// dispatch the payload to its handler, returns the response payload to reply.
+    pub async fn dispatch(&self, payload: &Payload) -> Option<Payload> {
+        let ty = payload
+            .metadata
+            .as_ref()
+            .map(|metadata| metadata.r#type.clone())
+            .unwrap_or_default();
+        let handler = self.handlers.read().unwrap().get(ty.as_str()).cloned();
+        let handler = match handler {
+            Some(handler) => handler,
+            None => {
+                log::warn!("no handler registered for server request type: {}", ty);
+                return None;
+            }
+        };
+        match handler.handle(payload).await {
+            Ok(response) => response,
+            Err(error) => {
+                log::warn!("handle server request {} error, {}", ty, error);
+                None
+            }
+        }
+    }
+}
+
+/// Handler of [ConnectResetRequest], which switches to the requested server
+/// or the next server in server list asynchronously.
+pub(crate) struct ConnectResetHandler(pub(crate) Weak<RpcClient>);
+
+#[async_trait::async_trait]
+impl ServerRequestHandler for ConnectResetHandler {
+    type Request = ConnectResetRequest;
+    type Response = ConnectResetResponse;
+
+    async fn request_reply(&self, request: Self::Request) -> NacosResult<Option<Self::Response>> {
+        let client = match self.0.upgrade() {
+            Some(client) => client,
+            None => return Ok(None),
+        };
+        if !client.is_shutdown() {
+            // switch to the next server if the recommended one is not valid.
+            let server_ip = request.server_ip.trim();
+            let server_info = match request.server_port.trim().parse::<u16>() {
+                Ok(server_port) if !server_ip.is_empty() => {
+                    Some(ServerInfo::new(server_ip.to_string(), server_port))
+                }
+                _ => None,
+            };
+            log::info!(
+                "server requests to reset connection, recommended server: {:?}",
+                server_info
+            );
+            client.switch_server_async(server_info, false);
+        }
+        Ok(Some(ConnectResetResponse {
+            inner: Default::default(),
+        }))
+    }
+}
+
+/// Handler of [ClientDetectionRequest], which tells server the client is alive.
+pub(crate) struct ClientDetectionHandler(pub(crate) Weak<RpcClient>);
+
+#[async_trait::async_trait]
+impl ServerRequestHandler for ClientDetectionHandler {
+    type Request = ClientDetectionRequest;
+    type Response = ClientDetectionResponse;
+
+    async fn request_reply(&self, _request: Self::Request) -> NacosResult<Option<Self::Response>> {
+        if let Some(client) = self.0.upgrade() {
+            if let Ok(connection) = client.current_connection() {
+                connection.fresh_active_time();
+            }
+        }
+        Ok(Some(ClientDetectionResponse {
+            inner: Default::default(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConnectResetHandler, ServerRequestHandler, ServerRequestHandlers};
+    use crate::common::remote::client::cli::RpcClient;
+    use crate::common::remote::client::grpc::utils::{convert_request, parse_response};
+    use nacos_api::api::remote::request::{ClientDetectionRequest, ConnectResetRequest};
+    use nacos_api::api::remote::response::{ClientDetectionResponse, ConnectResetResponse};
+    use nacos_core::error::NacosResult;
+    use std::sync::Arc;
+
+    struct DetectionHandler;
+
+    #[async_trait::async_trait]
+    impl ServerRequestHandler for DetectionHandler {
+        type Request = ClientDetectionRequest;
+        type Response = ClientDetectionResponse;
+
+        async fn request_reply(
+            &self,
+            _request: Self::Request,
+        ) -> NacosResult<Option<Self::Response>> {
+            Ok(Some(ClientDetectionResponse {
+                inner: Default::default(),
+            }))
+        }
+    }
+
+    #[test]
+    fn test_dispatch_typed_request() {
+        let handlers = ServerRequestHandlers::default();
+        handlers.register(DetectionHandler);
+        assert!(handlers.contains("ClientDetectionRequest"));
+        let mut request = ClientDetectionRequest {
+            inner: Default::default(),
+        };
+        request.request_id = Some("42".to_string());
+        let payload = convert_request(&request);
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let response = runtime.block_on(handlers.dispatch(&payload)).unwrap();
+        let response = parse_response::<ClientDetectionResponse>(&response).unwrap();
+        assert_eq!(response.request_id.as_deref(), Some("42"));
+        assert!(response.is_success());
+    }
+
+    #[test]
+    fn test_reply_connect_reset() {
+        let client = Arc::new(RpcClient::new(None, Default::default()));
+        let handlers = ServerRequestHandlers::default();
+        handlers.register(ConnectResetHandler(Arc::downgrade(&client)));
+        let request = ConnectResetRequest {
+            inner: Default::default(),
+            server_ip: "127.0.0.1".to_string(),
+            server_port: "8848".to_string(),
+        };
+        let payload = convert_request(&request);
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let response = runtime.block_on(handlers.dispatch(&payload)).unwrap();
+        let response = parse_response::<ConnectResetResponse>(&response).unwrap();
+        assert!(response.is_success());
+    }
+}
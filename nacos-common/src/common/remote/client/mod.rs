@@ -1,4 +1,6 @@
 pub mod cli;
 pub mod conn;
 pub mod grpc;
+pub mod handlers;
 pub mod meta;
+pub mod retry;
@@ -0,0 +1,155 @@
+//! A module to retry outbound requests with exponential backoff.
+use nacos_api::api::consts::val;
+use nacos_core::error::{NacosError, NacosResult};
+use rand::Rng;
+use std::future::Future;
+use std::time::Duration;
+
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(10);
+const DEFAULT_JITTER: f64 = 0.2;
+
+/// Policy to retry a failed call, the backoff doubles for each retry.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// max attempts of a call including the first one, `1` means no retry.
+    pub max_attempts: u32,
+    /// backoff before the first retry.
+    pub initial_backoff: Duration,
+    /// upper bound of backoff.
+    pub max_backoff: Duration,
+    /// random factor in `[0, 1]` applied to backoff, e.g. `0.2` means ±20%.
+    pub jitter: f64,
+    /// whether a failed call is worth retrying, [NacosError::is_retryable] by default.
+    pub retryable: fn(&NacosError) -> bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new(
+            val::MAX_RETRY as u32 + 1,
+            Duration::from_millis(val::CONFIG_RETRY_TIME as u64),
+        )
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32, initial_backoff: Duration) -> Self {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            jitter: DEFAULT_JITTER,
+            retryable: NacosError::is_retryable,
+        }
+    }
+
+    /// a policy never retrying.
+    pub fn no_retry() -> Self {
+        RetryPolicy::new(1, Duration::ZERO)
+    }
+
+    /// whether to retry after `attempts` calls failed, the last one with `error`.
+    pub fn should_retry(&self, error: &NacosError, attempts: u32) -> bool {
+        attempts < self.max_attempts && (self.retryable)(error)
+    }
+
+    /// backoff before the `retry`-th retry, counting from 1.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(31);
+        let backoff = self
+            .initial_backoff
+            .checked_mul(1 << exponent)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 || backoff.is_zero() {
+            return backoff;
+        }
+        let factor = rand::thread_rng().gen_range(1.0 - jitter..=1.0 + jitter);
+        backoff.mul_f64(factor)
+    }
+
+    /// run `call` until it succeeds or the policy gives up, `call` gets the count of
+    /// attempts failed before, so it may switch to another server for a retry.
+    pub async fn retry<T, F, Fut>(&self, mut call: F) -> NacosResult<T>
+    where
+        F: FnMut(u32) -> Fut,
+        Fut: Future<Output = NacosResult<T>>,
+    {
+        let mut attempts = 0;
+        loop {
+            let error = match call(attempts).await {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+            attempts += 1;
+            if !self.should_retry(&error, attempts) {
+                return Err(error);
+            }
+            let backoff = self.backoff(attempts);
+            log::warn!(
+                "attempt {} failed, retry after {}ms, {}",
+                attempts,
+                backoff.as_millis(),
+                error
+            );
+            tokio::time::sleep(backoff).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RetryPolicy;
+    use nacos_core::error::NacosError;
+    use std::time::Duration;
+
+    #[test]
+    fn test_backoff() {
+        let mut policy = RetryPolicy::new(5, Duration::from_millis(100));
+        policy.jitter = 0.0;
+        policy.max_backoff = Duration::from_millis(300);
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(300));
+        policy.jitter = 0.5;
+        for _ in 0..10 {
+            let backoff = policy.backoff(1);
+            assert!(backoff >= Duration::from_millis(50) && backoff <= Duration::from_millis(150));
+        }
+    }
+
+    #[test]
+    fn test_retry() {
+        let policy = RetryPolicy::new(3, Duration::from_millis(1));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let mut calls = vec![];
+        let result = runtime.block_on(policy.retry(|attempts| {
+            calls.push(attempts);
+            async move {
+                match attempts {
+                    0 => Err(NacosError::Timeout("timeout".to_string())),
+                    _ => Ok(attempts),
+                }
+            }
+        }));
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(calls, vec![0, 1]);
+        // an error not retryable fails at once.
+        let mut count = 0;
+        let result: Result<(), _> = runtime.block_on(policy.retry(|_| {
+            count += 1;
+            async { Err(NacosError::Forbidden("no right".to_string())) }
+        }));
+        assert!(result.is_err());
+        assert_eq!(count, 1);
+        // gives up after max attempts.
+        let mut count = 0;
+        let result: Result<(), _> = runtime.block_on(policy.retry(|_| {
+            count += 1;
+            async { Err(NacosError::Disconnected("closed".to_string())) }
+        }));
+        assert!(matches!(result, Err(NacosError::Disconnected(_))));
+        assert_eq!(count, 3);
+    }
+}
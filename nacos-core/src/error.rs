@@ -1,2 +1,257 @@
-pub type NacosResult<T> = anyhow::Result<T>;
-pub type NacosError = anyhow::Error;
+use std::fmt::{Display, Formatter};
+
+pub type NacosResult<T> = Result<T, NacosError>;
+
+/// Error codes of nacos, the positive ones are replied by server.
+pub mod codes {
+    pub const CLIENT_INVALID_PARAM: i32 = -400;
+    pub const CLIENT_DISCONNECT: i32 = -401;
+    /// no response from server in time.
+    pub const CLIENT_TIMEOUT: i32 = -408;
+    pub const CLIENT_OVER_THRESHOLD: i32 = -503;
+    pub const RESOURCE_NOT_FOUND: i32 = -404;
+    pub const HTTP_CLIENT_ERROR_CODE: i32 = -500;
+    pub const INVALID_PARAM: i32 = 400;
+    pub const NO_RIGHT: i32 = 403;
+    pub const NOT_FOUND: i32 = 404;
+    pub const CONFLICT: i32 = 409;
+    pub const TOO_MANY_REQUESTS: i32 = 429;
+    pub const SERVER_ERROR: i32 = 500;
+    pub const BAD_GATEWAY: i32 = 502;
+    pub const OVER_THRESHOLD: i32 = 503;
+}
+
+/// Errors of nacos client, see [NacosError::code] for the nacos error code of each kind.
+#[derive(Debug)]
+pub enum NacosError {
+    /// no response in time.
+    Timeout(String),
+    /// failed to connect to server, or the connection is lost.
+    Disconnected(String),
+    /// authentication failed or no permission.
+    Forbidden(String),
+    /// config or resource does not exist, `code` is the one replied by server,
+    /// e.g. `300` of a config query.
+    NotFound { code: i32, message: String },
+    /// cas publish is rejected as the config has changed on server.
+    CasMismatch(CasMismatchError),
+    /// server is overloaded or the request is rate limited.
+    ServerBusy(String),
+    /// other errors replied by server.
+    Server { code: i32, message: String },
+    /// failed to serialize or deserialize data.
+    Serialization(String),
+    /// invalid arguments or unexpected state on client side.
+    Client(String),
+}
+
+impl NacosError {
+    /// create an error of `code` replied by server, e.g. `ErrorResponse` or http status.
+    pub fn server<T: Display>(code: i32, message: T) -> Self {
+        let message = message.to_string();
+        match code {
+            codes::NO_RIGHT => NacosError::Forbidden(message),
+            codes::NOT_FOUND | codes::RESOURCE_NOT_FOUND => NacosError::NotFound { code, message },
+            codes::OVER_THRESHOLD | codes::TOO_MANY_REQUESTS | codes::CLIENT_OVER_THRESHOLD => {
+                NacosError::ServerBusy(message)
+            }
+            codes::CLIENT_DISCONNECT => NacosError::Disconnected(message),
+            code => NacosError::Server { code, message },
+        }
+    }
+
+    /// create an error of invalid arguments or unexpected state on client side.
+    pub fn client<T: Display>(message: T) -> Self {
+        NacosError::Client(message.to_string())
+    }
+
+    /// the nacos error code.
+    pub fn code(&self) -> i32 {
+        match self {
+            NacosError::Timeout(_) => codes::CLIENT_TIMEOUT,
+            NacosError::Disconnected(_) => codes::CLIENT_DISCONNECT,
+            NacosError::Forbidden(_) => codes::NO_RIGHT,
+            NacosError::NotFound { code, .. } => *code,
+            NacosError::CasMismatch(_) => codes::CONFLICT,
+            NacosError::ServerBusy(_) => codes::OVER_THRESHOLD,
+            NacosError::Server { code, .. } => *code,
+            NacosError::Serialization(_) => codes::HTTP_CLIENT_ERROR_CODE,
+            NacosError::Client(_) => codes::CLIENT_INVALID_PARAM,
+        }
+    }
+
+    /// whether the request may succeed if retried, maybe on another server.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            NacosError::Timeout(_) | NacosError::Disconnected(_) | NacosError::ServerBusy(_) => {
+                true
+            }
+            NacosError::Server { code, .. } => {
+                *code == codes::SERVER_ERROR || *code == codes::BAD_GATEWAY
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Display for NacosError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NacosError::Timeout(message) => write!(f, "timeout: {}", message),
+            NacosError::Disconnected(message) => write!(f, "disconnected: {}", message),
+            NacosError::Forbidden(message) => write!(f, "forbidden: {}", message),
+            NacosError::NotFound { message, .. } => write!(f, "not found: {}", message),
+            NacosError::CasMismatch(error) => write!(f, "{}", error),
+            NacosError::ServerBusy(message) => write!(f, "server busy: {}", message),
+            NacosError::Server { code, message } => {
+                write!(f, "server error, errorCode={}: {}", code, message)
+            }
+            NacosError::Serialization(message) => write!(f, "serialization error: {}", message),
+            NacosError::Client(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for NacosError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NacosError::CasMismatch(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<CasMismatchError> for NacosError {
+    fn from(error: CasMismatchError) -> Self {
+        NacosError::CasMismatch(error)
+    }
+}
+
+impl From<tonic::Status> for NacosError {
+    fn from(status: tonic::Status) -> Self {
+        let message = status.message().to_string();
+        match status.code() {
+            tonic::Code::DeadlineExceeded => NacosError::Timeout(message),
+            tonic::Code::Unavailable | tonic::Code::Cancelled | tonic::Code::Aborted => {
+                NacosError::Disconnected(message)
+            }
+            tonic::Code::PermissionDenied | tonic::Code::Unauthenticated => {
+                NacosError::Forbidden(message)
+            }
+            tonic::Code::NotFound => NacosError::NotFound {
+                code: codes::NOT_FOUND,
+                message,
+            },
+            tonic::Code::ResourceExhausted => NacosError::ServerBusy(message),
+            tonic::Code::InvalidArgument => NacosError::Client(message),
+            _ => NacosError::Server {
+                code: codes::SERVER_ERROR,
+                message,
+            },
+        }
+    }
+}
+
+impl From<tonic::transport::Error> for NacosError {
+    fn from(error: tonic::transport::Error) -> Self {
+        NacosError::Disconnected(error.to_string())
+    }
+}
+
+impl From<reqwest::Error> for NacosError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            NacosError::Timeout(error.to_string())
+        } else if let Some(status) = error.status() {
+            NacosError::server(status.as_u16() as i32, error)
+        } else if error.is_decode() {
+            NacosError::Serialization(error.to_string())
+        } else if error.is_builder() {
+            NacosError::Client(error.to_string())
+        } else {
+            NacosError::Disconnected(error.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for NacosError {
+    fn from(error: serde_json::Error) -> Self {
+        NacosError::Serialization(error.to_string())
+    }
+}
+
+/// Local I/O errors other than network ones are client errors, e.g. a missing local file
+/// is not a config missing on server.
+impl From<std::io::Error> for NacosError {
+    fn from(error: std::io::Error) -> Self {
+        use std::io::ErrorKind;
+        match error.kind() {
+            ErrorKind::TimedOut => NacosError::Timeout(error.to_string()),
+            ErrorKind::ConnectionRefused
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NotConnected
+            | ErrorKind::BrokenPipe => NacosError::Disconnected(error.to_string()),
+            _ => NacosError::Client(error.to_string()),
+        }
+    }
+}
+
+/// Error returned when a cas publish is rejected because the config md5 on server has changed.
+#[derive(Debug, Clone)]
+pub struct CasMismatchError {
+    pub data_id: String,
+    pub group: String,
+    pub message: String,
+}
+
+impl Display for CasMismatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "cas publish failed, dataId={}, group={}: {}",
+            self.data_id, self.group, self.message
+        )
+    }
+}
+
+impl std::error::Error for CasMismatchError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{codes, NacosError};
+
+    #[test]
+    fn test_server_error_code() {
+        let error = NacosError::server(codes::NO_RIGHT, "unknown user");
+        assert!(matches!(error, NacosError::Forbidden(_)));
+        assert_eq!(error.code(), codes::NO_RIGHT);
+        assert!(!error.is_retryable());
+        let error = NacosError::server(codes::OVER_THRESHOLD, "too many connections");
+        assert!(error.is_retryable());
+        let error = NacosError::server(codes::RESOURCE_NOT_FOUND, "no such resource");
+        assert!(matches!(error, NacosError::NotFound { .. }));
+        assert_eq!(error.code(), codes::RESOURCE_NOT_FOUND);
+        let error = NacosError::server(codes::BAD_GATEWAY, "bad gateway");
+        assert_eq!(error.code(), codes::BAD_GATEWAY);
+        assert!(error.is_retryable());
+    }
+
+    #[test]
+    fn test_from_status() {
+        let error = NacosError::from(tonic::Status::deadline_exceeded("no response"));
+        assert!(matches!(error, NacosError::Timeout(_)));
+        assert!(error.is_retryable());
+        let error = NacosError::from(tonic::Status::permission_denied("no right"));
+        assert!(matches!(error, NacosError::Forbidden(_)));
+    }
+
+    #[test]
+    fn test_from_io_error() {
+        use std::io::{Error, ErrorKind};
+        let error = NacosError::from(Error::new(ErrorKind::NotFound, "no such file"));
+        assert!(matches!(error, NacosError::Client(_)));
+        let error = NacosError::from(Error::new(ErrorKind::ConnectionReset, "reset"));
+        assert!(matches!(error, NacosError::Disconnected(_)));
+    }
+}